@@ -1,4 +1,4 @@
-use critter::{auth::TwitterAuth, TwitterClient};
+use critter::{auth::TwitterAuth, fields::UserField, TwitterClient};
 use std::env;
 
 #[tokio::main]
@@ -10,7 +10,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         &env::var("ACCESS_TOKEN_SECRET").unwrap(),
     );
 
-    let mut twitter = TwitterClient::new(auth)?;
+    let twitter = TwitterClient::new(auth)?;
 
     // Print all the default fields
     match twitter.me(None).await {
@@ -24,19 +24,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Request the description
-    match twitter.me(Some(&["description"])).await {
+    match twitter.me(Some(&[UserField::Description])).await {
         Ok(data) => println!("My description is \"{}\"", data.description()),
         Err(e) => println!("Error: {}", e),
     }
 
     // Request the date your account was created at
-    match twitter.me(Some(&["created_at"])).await {
+    match twitter.me(Some(&[UserField::CreatedAt])).await {
         Ok(data) => println!("I made my account on {}", data.created_at()),
         Err(e) => println!("Error: {}", e),
     }
 
     // Request multiple fields
-    match twitter.me(Some(&["description", "created_at"])).await {
+    match twitter
+        .me(Some(&[UserField::Description, UserField::CreatedAt]))
+        .await
+    {
         Ok(data) => println!(
             "My description is \"{}\" and I made my account on {}",
             data.description(),
@@ -0,0 +1,59 @@
+use crate::error::Error;
+
+use reqwest::{Client, RequestBuilder};
+use std::future::Future;
+use std::pin::Pin;
+
+/// What is known about a request when it gets authenticated.
+#[derive(Debug, Clone, Copy)]
+pub struct AuthRequest<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub query: Option<&'a [(&'a str, &'a str)]>,
+    /// `application/x-www-form-urlencoded` body parameters, if any.
+    pub form: Option<&'a [(&'a str, &'a str)]>,
+}
+
+/// The HTTP client and api base url that token requests are sent with. A
+/// [`TwitterClient`](crate::TwitterClient) passes its own, so they go through the
+/// same proxy and timeouts as every other request.
+#[derive(Debug, Clone)]
+pub struct AuthHttp {
+    pub http: Client,
+    /// e.g. `https://api.twitter.com`, without a trailing slash.
+    pub api_url: String,
+}
+impl Default for AuthHttp {
+    fn default() -> AuthHttp {
+        AuthHttp {
+            http: Client::new(),
+            api_url: "https://api.twitter.com".into(),
+        }
+    }
+}
+
+pub type RefreshFuture<'a> = Pin<Box<dyn Future<Output = Result<bool, Error>> + Send + 'a>>;
+
+/// Attaches credentials to every request made by [`TwitterClient`](crate::TwitterClient).
+///
+/// [`TwitterAuth`](super::TwitterAuth) implements this for the built-in schemes, implement
+/// it yourself to sign requests some other way.
+pub trait Authenticator: Send + Sync {
+    fn authenticate(
+        &self,
+        request: RequestBuilder,
+        info: &AuthRequest<'_>,
+    ) -> Result<RequestBuilder, Error>;
+
+    /// Called when a request comes back with `401 Unauthorized`. Resolve to `true` if the
+    /// credentials were renewed and the request should be sent again. `authorization` is
+    /// the `Authorization` header the rejected request was sent with, so concurrent
+    /// failures can tell whether someone else renewed the credentials in the meantime.
+    fn refresh<'a>(
+        &'a self,
+        http: &'a AuthHttp,
+        authorization: Option<&'a str>,
+    ) -> RefreshFuture<'a> {
+        Box::pin(async { Ok(false) })
+    }
+}
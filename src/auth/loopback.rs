@@ -0,0 +1,261 @@
+use super::{AuthHttp, TwitterAuth, TwitterOAuth2Flow};
+use crate::error::Error;
+
+use futures::future::select_all;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const RESPONSE: &str =
+    "<html><body>Authorization complete, you can close this window.</body></html>";
+
+type UrlHook = Box<dyn Fn(&str) + Send + Sync>;
+
+/// How the authorization url is shown and how long to wait for the redirect.
+pub struct LoopbackOptions {
+    timeout: Duration,
+    open_browser: bool,
+    on_url: Option<UrlHook>,
+    http: AuthHttp,
+}
+impl LoopbackOptions {
+    /// How long to wait for the browser to be redirected back, 5 minutes by default.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Whether to open the authorization url in the default browser, `true` by default.
+    pub fn open_browser(&mut self, open_browser: bool) -> &mut Self {
+        self.open_browser = open_browser;
+        self
+    }
+
+    /// Called with the authorization url before the browser is opened, e.g. to
+    /// show it to the user in case no browser can be opened.
+    pub fn on_url<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.on_url = Some(Box::new(f));
+        self
+    }
+
+    /// The HTTP client and api url the OAuth 1.0a token requests are sent with.
+    /// For OAuth 2.0 set them on the flow with [`TwitterOAuth2Flow::http`].
+    pub fn http(&mut self, http: AuthHttp) -> &mut Self {
+        self.http = http;
+        self
+    }
+
+    fn show(&self, url: &str) -> Result<(), Error> {
+        if let Some(on_url) = &self.on_url {
+            on_url(url);
+        }
+
+        if !self.open_browser {
+            return Ok(());
+        }
+
+        match webbrowser::open(url) {
+            // without a hook the user has no other way to get to the url
+            Err(err) if self.on_url.is_none() => Err(Error::Io(err)),
+            _ => Ok(()),
+        }
+    }
+}
+impl Default for LoopbackOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5 * 60),
+            open_browser: true,
+            on_url: None,
+            http: AuthHttp::default(),
+        }
+    }
+}
+
+/// Runs the three-legged OAuth 1.0a flow in the browser, using
+/// `http://127.0.0.1:{port}/callback` as the callback url. That url has to be
+/// registered as a callback for the app in the developer portal.
+pub async fn authorize_oauth1<F>(
+    consumer_key: &str,
+    consumer_secret: &str,
+    port: u16,
+    f: F,
+) -> Result<TwitterAuth, Error>
+where
+    F: FnOnce(&mut LoopbackOptions) -> &mut LoopbackOptions,
+{
+    let mut options = LoopbackOptions::default();
+    f(&mut options);
+
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+    let callback = format!(
+        "http://127.0.0.1:{}/callback",
+        listener.local_addr()?.port()
+    );
+
+    let request = TwitterAuth::request_token_with(
+        options.http.clone(),
+        consumer_key,
+        consumer_secret,
+        Some(&callback),
+    )
+    .await?;
+    options.show(&request.authorize_url())?;
+
+    // the request token doubles as the state of the flow
+    let token = Some(request.oauth_token());
+    let params = wait_for_callback(&[listener], "/callback", options.timeout, |params| {
+        params.get("oauth_token").map(String::as_str) == token
+            || params.get("denied").map(String::as_str) == token
+    })
+    .await?;
+    if params.contains_key("denied") {
+        return Err(Error::AuthorizationDenied);
+    }
+
+    match params.get("oauth_verifier") {
+        Some(verifier) => request.access_token(verifier).await,
+        None => Err(Error::AuthorizationDenied),
+    }
+}
+
+/// Runs the OAuth 2.0 Authorization Code flow in the browser. The redirect uri of
+/// the flow has to point at `127.0.0.1`, `[::1]` or `localhost` with an explicit port.
+pub async fn authorize_oauth2<F>(flow: &TwitterOAuth2Flow, f: F) -> Result<TwitterAuth, Error>
+where
+    F: FnOnce(&mut LoopbackOptions) -> &mut LoopbackOptions,
+{
+    let mut options = LoopbackOptions::default();
+    f(&mut options);
+
+    let invalid = || Error::InvalidUrl(flow.redirect_uri().into());
+    let redirect = Url::parse(flow.redirect_uri()).map_err(|_| invalid())?;
+    let port = redirect.port().ok_or_else(invalid)?;
+
+    // `localhost` can resolve to either address, so listen on both
+    let listeners = match redirect.host_str() {
+        Some("127.0.0.1") => vec![TcpListener::bind(("127.0.0.1", port)).await?],
+        Some("[::1]") => vec![TcpListener::bind(("::1", port)).await?],
+        Some("localhost") => {
+            let mut listeners = vec![TcpListener::bind(("127.0.0.1", port)).await?];
+            // not every machine has IPv6
+            if let Ok(listener) = TcpListener::bind(("::1", port)).await {
+                listeners.push(listener);
+            }
+            listeners
+        }
+        _ => return Err(invalid()),
+    };
+    options.show(&flow.authorize_url())?;
+
+    let params = wait_for_callback(&listeners, redirect.path(), options.timeout, |params| {
+        params.get("state").map(String::as_str) == Some(flow.state())
+    })
+    .await?;
+    if params.contains_key("error") {
+        return Err(Error::AuthorizationDenied);
+    }
+
+    match params.get("code") {
+        Some(code) => flow.exchange(code).await,
+        None => Err(Error::AuthorizationDenied),
+    }
+}
+
+/// Waits for a request to `path` whose query `is_callback` accepts. Anything else,
+/// e.g. a stale tab of an earlier attempt, gets an error page and is ignored.
+async fn wait_for_callback<F>(
+    listeners: &[TcpListener],
+    path: &str,
+    timeout: Duration,
+    is_callback: F,
+) -> Result<HashMap<String, String>, Error>
+where
+    F: Fn(&HashMap<String, String>) -> bool,
+{
+    tokio::time::timeout(timeout, accept_callback(listeners, path, is_callback))
+        .await
+        .map_err(|_| {
+            Error::Io(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "no authorization callback was received",
+            ))
+        })?
+}
+
+async fn accept_callback<F>(
+    listeners: &[TcpListener],
+    path: &str,
+    is_callback: F,
+) -> Result<HashMap<String, String>, Error>
+where
+    F: Fn(&HashMap<String, String>) -> bool,
+{
+    loop {
+        let (accepted, _, _) =
+            select_all(listeners.iter().map(|listener| Box::pin(listener.accept()))).await;
+        let (mut stream, _) = accepted?;
+
+        // a connection that breaks off isn't the browser coming back
+        let Some(request) = read_request(&mut stream).await else {
+            continue;
+        };
+
+        // GET /callback?oauth_token=...&oauth_verifier=... HTTP/1.1
+        let url = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|target| Url::parse(&format!("http://127.0.0.1{}", target)).ok());
+
+        let status = match url {
+            Some(url) if url.path() == path => {
+                let params = url.query_pairs().into_owned().collect::<HashMap<_, _>>();
+                if is_callback(&params) {
+                    respond(&mut stream, "200 OK", RESPONSE).await;
+                    return Ok(params);
+                }
+
+                "400 Bad Request"
+            }
+            // browsers also ask for things like /favicon.ico
+            _ => "404 Not Found",
+        };
+        respond(&mut stream, status, "").await;
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<String> {
+    let mut buf = vec![0u8; 8192];
+    let mut len = 0;
+    while len < buf.len() {
+        let n = stream.read(&mut buf[len..]).await.ok()?;
+        len += n;
+        if n == 0 || buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+// the browser closing the tab early doesn't matter to the flow
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let _ = stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .as_bytes(),
+        )
+        .await;
+}
@@ -1,17 +1,28 @@
+mod authenticator;
 mod oauth1;
+mod oauth2;
 
+#[cfg(feature = "loopback")]
+pub mod loopback;
+
+pub use self::authenticator::*;
 pub use self::oauth1::*;
+pub use self::oauth2::*;
 
-use rand::distributions::Alphanumeric;
-use rand::Rng;
+use crate::error::Error;
 
-use std::time::{SystemTime, UNIX_EPOCH};
-use base64::{engine::general_purpose, Engine as _};
-use crypto::{hmac::Hmac, mac::Mac, sha1::Sha1};
+use reqwest::{header::AUTHORIZATION, RequestBuilder};
+use std::sync::{Arc, Mutex};
 
+/// Clones share the OAuth 2.0 tokens, so a refresh done by the client is visible
+/// through every clone.
 #[derive(Debug, Clone)]
 pub struct TwitterAuth {
-    pin3: Option<Twitter3Pin>
+    pin3: Option<Twitter3Pin>,
+    bearer: Option<TwitterBearer>,
+    oauth2: Option<Arc<Mutex<TwitterOAuth2>>>,
+    /// Held for the whole refresh, so concurrent `401`s renew the tokens only once.
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
 }
 impl TwitterAuth {
     pub fn from_oa1uc(
@@ -26,72 +37,216 @@ impl TwitterAuth {
                 consumer_secret: consumer_secret.into(),
                 access_token: access_token.into(),
                 access_token_secret: access_token_secret.into() 
-            })
+            }),
+            bearer: None,
+            oauth2: None,
+            refresh_lock: Arc::default(),
+        }
+    }
+
+    /// First step of the three-legged OAuth 1.0a flow. Pass `None` as the callback
+    /// for the PIN (`oob`) flow.
+    pub async fn request_token(
+        consumer_key: &str,
+        consumer_secret: &str,
+        callback: Option<&str>,
+    ) -> Result<Twitter3PinRequest, Error> {
+        Twitter3PinRequest::new(consumer_key, consumer_secret, callback).await
+    }
+
+    /// Like [`TwitterAuth::request_token`], sending the token requests with `http`.
+    pub async fn request_token_with(
+        http: AuthHttp,
+        consumer_key: &str,
+        consumer_secret: &str,
+        callback: Option<&str>,
+    ) -> Result<Twitter3PinRequest, Error> {
+        Twitter3PinRequest::new_with(http, consumer_key, consumer_secret, callback).await
+    }
+
+    /// OAuth 2.0 App-only authentication with an already issued bearer token.
+    pub fn from_bearer(token: &str) -> Self {
+        Self {
+            pin3: None,
+            bearer: Some(TwitterBearer {
+                token: token.into(),
+            }),
+            oauth2: None,
+            refresh_lock: Arc::default(),
+        }
+    }
+
+    /// OAuth 2.0 App-only authentication. The bearer token is fetched once from
+    /// `/oauth2/token` and reused for every request.
+    pub async fn from_app_credentials(
+        consumer_key: &str,
+        consumer_secret: &str,
+    ) -> Result<Self, Error> {
+        Self::from_app_credentials_with(&AuthHttp::default(), consumer_key, consumer_secret).await
+    }
+
+    /// Like [`TwitterAuth::from_app_credentials`], sending the token request with `http`.
+    pub async fn from_app_credentials_with(
+        http: &AuthHttp,
+        consumer_key: &str,
+        consumer_secret: &str,
+    ) -> Result<Self, Error> {
+        let bearer = TwitterBearer::request_with(http, consumer_key, consumer_secret).await?;
+
+        Ok(Self {
+            pin3: None,
+            bearer: Some(bearer),
+            oauth2: None,
+            refresh_lock: Arc::default(),
+        })
+    }
+
+    /// OAuth 2.0 Authorization Code with PKCE (user context) with already issued
+    /// tokens. Use [`TwitterOAuth2Flow`] to obtain them.
+    pub fn from_oa2uc(
+        client_id: &str,
+        client_secret: Option<&str>,
+        access_token: &str,
+        refresh_token: Option<&str>,
+    ) -> Self {
+        Self::from_oauth2(TwitterOAuth2 {
+            client_id: client_id.into(),
+            client_secret: client_secret.map(Into::into),
+            access_token: access_token.into(),
+            refresh_token: refresh_token.map(Into::into),
+            expires_at: None,
+        })
+    }
+
+    pub(crate) fn from_oauth2(oauth2: TwitterOAuth2) -> Self {
+        Self {
+            pin3: None,
+            bearer: None,
+            oauth2: Some(Arc::new(Mutex::new(oauth2))),
+            refresh_lock: Arc::default(),
+        }
+    }
+
+    /// The current OAuth 2.0 user context tokens, e.g. to persist them after a refresh.
+    pub fn oauth2(&self) -> Option<TwitterOAuth2> {
+        self.oauth2
+            .as_ref()
+            .map(|oauth2| oauth2.lock().expect("poisoned oauth2 lock").clone())
+    }
+
+    pub fn can_refresh(&self) -> bool {
+        self.oauth2()
+            .is_some_and(|oauth2| oauth2.refresh_token.is_some())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.oauth2().is_some_and(|oauth2| oauth2.is_expired())
+    }
+
+    pub async fn refresh(&self) -> Result<(), Error> {
+        self.refresh_with(&AuthHttp::default()).await
+    }
+
+    /// Like [`TwitterAuth::refresh`], sending the token request with `http`.
+    pub async fn refresh_with(&self, http: &AuthHttp) -> Result<(), Error> {
+        let _guard = self.refresh_lock.lock().await;
+        self.refresh_locked(http).await
+    }
+
+    async fn refresh_locked(&self, http: &AuthHttp) -> Result<(), Error> {
+        let (Some(shared), Some(mut oauth2)) = (&self.oauth2, self.oauth2()) else {
+            return Err(Error::BadCredentials);
+        };
+
+        oauth2.refresh_with(http).await?;
+        *shared.lock().expect("poisoned oauth2 lock") = oauth2;
+
+        Ok(())
+    }
+
+    /// Whether the OAuth 2.0 access token is no longer the one `authorization` was made with.
+    fn renewed_since(&self, authorization: Option<&str>) -> bool {
+        match (self.oauth2(), authorization) {
+            (Some(oauth2), Some(authorization)) => {
+                authorization != format!("Bearer {}", oauth2.access_token)
+            }
+            _ => false,
         }
     }
 
+    pub fn bearer_token(&self) -> Option<&str> {
+        self.bearer.as_ref().map(|bearer| bearer.token.as_str())
+    }
+
     pub fn header(
-        &mut self,
+        &self,
         method: &str,
         url: &str,
-        //parameters: Vec<&str>,
         query: Option<&[(&str, &str)]>
+    ) -> String {
+        self.header_with_form(method, url, query, None)
+    }
+
+    /// Like [`TwitterAuth::header`], for requests with an `application/x-www-form-urlencoded`
+    /// body. OAuth 1.0a signs the form parameters too.
+    pub fn header_with_form(
+        &self,
+        method: &str,
+        url: &str,
+        query: Option<&[(&str, &str)]>,
+        form: Option<&[(&str, &str)]>
     ) -> String {
         if let Some(pin3) = &self.pin3 {
-            let nonce: String = rand::thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(42)
-                .map(char::from)
-                .collect();
-            let time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("unix failed??")
-                .as_secs()
-                .to_string();
-
-            let mut parameter_string = format!("oauth_consumer_key={}&oauth_nonce={}&oauth_signature_method=HMAC-SHA1&oauth_timestamp={}&oauth_token={}&oauth_version=1.0", 
-                &pin3.consumer_key,
-                &nonce, 
-                &time,
-                &pin3.access_token
-            );
-            if let Some(q) = query {
-                let mut to_sort = q.iter()
-                    .map(|(key, value)| format!("{}={}", urlencoding::encode(key), urlencoding::encode(value)))
-                    .collect::<Vec<String>>();
-                to_sort.push(parameter_string.clone());
-                to_sort.sort();
-
-                parameter_string = to_sort.join("&");
-            }
-            //}
-
-            //println!("parameter string: {}", parameter_string);
-            
-            let sig_base_string = format!("{}&{}&{}",
-                &method,
-                urlencoding::encode(url),
-                urlencoding::encode(&parameter_string)
-            );
-
-            //println!("sig_base_string: {}", sig_base_string);
-
-            //println!("base string: {}", sig_base_string);
-
-            let mut hmac = Hmac::new(Sha1::new(), format!("{}&{}", &pin3.consumer_secret, &pin3.access_token_secret).as_bytes());
-            hmac.input(sig_base_string.as_bytes());
-            let result = hmac.result();
-            let code = result.code();
-
-            format!("OAuth oauth_consumer_key=\"{}\",oauth_nonce=\"{}\",oauth_signature=\"{}\",oauth_signature_method=\"HMAC-SHA1\",oauth_timestamp=\"{}\",oauth_token=\"{}\",oauth_version=\"1.0\"", 
-                &pin3.consumer_key,
-                &nonce,
-                urlencoding::encode(&general_purpose::STANDARD.encode(code)),
-                &time,
-                &pin3.access_token
+            oauth1_header(
+                method,
+                url,
+                (&pin3.consumer_key, &pin3.consumer_secret),
+                Some((&pin3.access_token, &pin3.access_token_secret)),
+                &[],
+                query,
+                form,
             )
+        } else if let Some(bearer) = &self.bearer {
+            format!("Bearer {}", &bearer.token)
+        } else if let Some(oauth2) = self.oauth2() {
+            format!("Bearer {}", &oauth2.access_token)
         } else {
             String::new()
         }
     }
-}
\ No newline at end of file
+}
+
+impl Authenticator for TwitterAuth {
+    fn authenticate(
+        &self,
+        request: RequestBuilder,
+        info: &AuthRequest<'_>,
+    ) -> Result<RequestBuilder, Error> {
+        Ok(request.header(
+            AUTHORIZATION,
+            self.header_with_form(info.method, info.url, info.query, info.form),
+        ))
+    }
+
+    fn refresh<'a>(
+        &'a self,
+        http: &'a AuthHttp,
+        authorization: Option<&'a str>,
+    ) -> RefreshFuture<'a> {
+        Box::pin(async move {
+            if !self.can_refresh() {
+                return Ok(false);
+            }
+
+            let _guard = self.refresh_lock.lock().await;
+            // another request already got a new token while this one waited for the
+            // lock, retry with it instead of spending the refresh token again
+            if self.renewed_since(authorization) {
+                return Ok(true);
+            }
+
+            self.refresh_locked(http).await?;
+            Ok(true)
+        })
+    }
+}
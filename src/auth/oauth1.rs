@@ -1,3 +1,14 @@
+use super::{AuthHttp, TwitterAuth};
+use crate::error::Error;
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::Url;
+
+use base64::{engine::general_purpose, Engine as _};
+use crypto::{hmac::Hmac, mac::Mac, sha1::Sha1};
+use std::time::{SystemTime, UNIX_EPOCH};
+
 #[derive(Debug, Clone)]
 pub struct Twitter3Pin {
     pub consumer_key: String,
@@ -5,3 +16,415 @@ pub struct Twitter3Pin {
     pub access_token: String,
     pub access_token_secret: String,
 }
+
+/// A temporary request token, the first step of the three-legged (PIN or callback) flow.
+#[derive(Debug, Clone)]
+pub struct Twitter3PinRequest {
+    consumer_key: String,
+    consumer_secret: String,
+    oauth_token: String,
+    oauth_token_secret: String,
+    callback_confirmed: bool,
+    http: AuthHttp,
+}
+impl Twitter3PinRequest {
+    pub async fn new(
+        consumer_key: &str,
+        consumer_secret: &str,
+        callback: Option<&str>,
+    ) -> Result<Self, Error> {
+        Self::new_with(AuthHttp::default(), consumer_key, consumer_secret, callback).await
+    }
+
+    /// Like [`Twitter3PinRequest::new`], sending the token requests with `http`.
+    pub async fn new_with(
+        http: AuthHttp,
+        consumer_key: &str,
+        consumer_secret: &str,
+        callback: Option<&str>,
+    ) -> Result<Self, Error> {
+        let url = format!("{}/oauth/request_token", http.api_url);
+        let header = oauth1_header(
+            "POST",
+            &url,
+            (consumer_key, consumer_secret),
+            None,
+            &[("oauth_callback", callback.unwrap_or("oob"))],
+            None,
+            None,
+        );
+
+        let res = post_form(&http, &url, header).await?;
+
+        match (get(&res, "oauth_token"), get(&res, "oauth_token_secret")) {
+            (Some(oauth_token), Some(oauth_token_secret)) => Ok(Self {
+                consumer_key: consumer_key.into(),
+                consumer_secret: consumer_secret.into(),
+                oauth_token,
+                oauth_token_secret,
+                callback_confirmed: get(&res, "oauth_callback_confirmed").as_deref()
+                    == Some("true"),
+                http,
+            }),
+            _ => Err(Error::BadCredentials),
+        }
+    }
+
+    pub fn oauth_token(&self) -> &str {
+        &self.oauth_token
+    }
+
+    pub fn callback_confirmed(&self) -> bool {
+        self.callback_confirmed
+    }
+
+    /// Where the user has to go to authorize the app. With `oob` they are shown a PIN,
+    /// otherwise they are redirected to the callback with `oauth_verifier` in the query.
+    pub fn authorize_url(&self) -> String {
+        format!(
+            "{}/oauth/authorize?oauth_token={}",
+            self.http.api_url,
+            urlencoding::encode(&self.oauth_token)
+        )
+    }
+
+    /// Exchanges the PIN or `oauth_verifier` for the user's access token.
+    pub async fn access_token(&self, verifier: &str) -> Result<TwitterAuth, Error> {
+        let url = format!("{}/oauth/access_token", self.http.api_url);
+        let header = oauth1_header(
+            "POST",
+            &url,
+            (&self.consumer_key, &self.consumer_secret),
+            Some((&self.oauth_token, &self.oauth_token_secret)),
+            &[("oauth_verifier", verifier)],
+            None,
+            None,
+        );
+
+        let res = post_form(&self.http, &url, header).await?;
+
+        match (get(&res, "oauth_token"), get(&res, "oauth_token_secret")) {
+            (Some(access_token), Some(access_token_secret)) => Ok(TwitterAuth::from_oa1uc(
+                &self.consumer_key,
+                &self.consumer_secret,
+                &access_token,
+                &access_token_secret,
+            )),
+            _ => Err(Error::BadCredentials),
+        }
+    }
+}
+
+async fn post_form(http: &AuthHttp, url: &str, header: String) -> Result<String, Error> {
+    let res = http
+        .http
+        .post(url)
+        .header(reqwest::header::AUTHORIZATION, header)
+        .send()
+        .await?;
+
+    if !res.status().is_success() {
+        return Err(Error::BadCredentials);
+    }
+
+    Ok(res.text().await?)
+}
+
+fn get(form: &str, key: &str) -> Option<String> {
+    form.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .and_then(|(_, v)| urlencoding::decode(v).ok())
+        .map(|v| v.into_owned())
+}
+
+pub(crate) fn oauth1_header(
+    method: &str,
+    url: &str,
+    consumer: (&str, &str),
+    token: Option<(&str, &str)>,
+    oauth_extra: &[(&str, &str)],
+    query: Option<&[(&str, &str)]>,
+    form: Option<&[(&str, &str)]>,
+) -> String {
+    let nonce: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(42)
+        .map(char::from)
+        .collect();
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("unix failed??")
+        .as_secs()
+        .to_string();
+
+    let mut oauth_params = vec![
+        ("oauth_consumer_key", consumer.0),
+        ("oauth_nonce", nonce.as_str()),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", time.as_str()),
+        ("oauth_version", "1.0"),
+    ];
+    if let Some((access_token, _)) = token {
+        oauth_params.push(("oauth_token", access_token));
+    }
+    oauth_params.extend_from_slice(oauth_extra);
+
+    sign_header(
+        method,
+        url,
+        &oauth_params,
+        (consumer.1, token.map_or("", |(_, secret)| secret)),
+        query,
+        form,
+    )
+}
+
+// `secrets` are the consumer and token secret
+fn sign_header(
+    method: &str,
+    url: &str,
+    oauth_params: &[(&str, &str)],
+    secrets: (&str, &str),
+    query: Option<&[(&str, &str)]>,
+    form: Option<&[(&str, &str)]>,
+) -> String {
+    let params = oauth_params
+        .iter()
+        .chain(query.unwrap_or_default())
+        .chain(form.unwrap_or_default())
+        .copied()
+        .collect::<Vec<_>>();
+    let signature = signature(
+        &signature_base_string(method, url, &params),
+        secrets.0,
+        secrets.1,
+    );
+
+    let mut oauth_params = oauth_params.to_vec();
+    oauth_params.push(("oauth_signature", &signature));
+    oauth_params.sort();
+
+    format!(
+        "OAuth {}",
+        oauth_params
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", percent_encode(key), percent_encode(value)))
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
+/// Percent-encodes everything except the unreserved characters of RFC 3986
+/// (RFC 5849 section 3.6).
+pub fn percent_encode(s: &str) -> String {
+    urlencoding::encode(s).into_owned()
+}
+
+/// Builds the signature base string of RFC 5849 section 3.4.1. `params` are all the
+/// `oauth_*` protocol parameters together with the query and form body parameters.
+/// Any query already in `url` is moved into the parameters.
+pub fn signature_base_string(method: &str, url: &str, params: &[(&str, &str)]) -> String {
+    let (base_url, url_query) = match Url::parse(url) {
+        Ok(parsed) => {
+            let query = parsed
+                .query_pairs()
+                .map(|(key, value)| (percent_encode(&key), percent_encode(&value)))
+                .collect::<Vec<_>>();
+            (base_string_uri(&parsed), query)
+        }
+        Err(_) => (url.to_string(), Vec::new()),
+    };
+
+    let mut normalized = params
+        .iter()
+        .map(|(key, value)| (percent_encode(key), percent_encode(value)))
+        .chain(url_query)
+        .collect::<Vec<_>>();
+    normalized.sort();
+
+    let parameter_string = normalized
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<String>>()
+        .join("&");
+
+    format!(
+        "{}&{}&{}",
+        method.to_ascii_uppercase(),
+        percent_encode(&base_url),
+        percent_encode(&parameter_string)
+    )
+}
+
+/// HMAC-SHA1 signature of a base string (RFC 5849 section 3.4.2), base64 encoded.
+pub fn signature(base_string: &str, consumer_secret: &str, token_secret: &str) -> String {
+    let key = format!(
+        "{}&{}",
+        percent_encode(consumer_secret),
+        percent_encode(token_secret)
+    );
+
+    let mut hmac = Hmac::new(Sha1::new(), key.as_bytes());
+    hmac.input(base_string.as_bytes());
+
+    general_purpose::STANDARD.encode(hmac.result().code())
+}
+
+// scheme and host lowercase, default ports dropped, no query or fragment
+fn base_string_uri(url: &Url) -> String {
+    let scheme = url.scheme().to_ascii_lowercase();
+    let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+
+    match url.port() {
+        Some(port) => format!("{}://{}:{}{}", scheme, host, port, url.path()),
+        None => format!("{}://{}{}", scheme, host, url.path()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://developer.twitter.com/en/docs/authentication/oauth-1-0a/creating-a-signature
+    const TWITTER_URL: &str = "https://api.twitter.com/1.1/statuses/update.json";
+    const TWITTER_QUERY: &[(&str, &str)] = &[("include_entities", "true")];
+    const TWITTER_FORM: &[(&str, &str)] = &[(
+        "status",
+        "Hello Ladies + Gentlemen, a signed OAuth request!",
+    )];
+    const TWITTER_OAUTH: &[(&str, &str)] = &[
+        ("oauth_consumer_key", "xvz1evFS4wEEPTGEFPHBog"),
+        ("oauth_nonce", "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg"),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", "1318622958"),
+        (
+            "oauth_token",
+            "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+        ),
+        ("oauth_version", "1.0"),
+    ];
+    const TWITTER_SECRETS: (&str, &str) = (
+        "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+        "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+    );
+
+    #[test]
+    fn rfc5849_base_string() {
+        // RFC 5849 section 3.4.1.1, the query stays in the url
+        let params = [
+            ("oauth_consumer_key", "9djdj82h48djs9d2"),
+            ("oauth_token", "kkk9d7dh3k39sjv7"),
+            ("oauth_signature_method", "HMAC-SHA1"),
+            ("oauth_timestamp", "137131201"),
+            ("oauth_nonce", "7d8f3e4a"),
+            ("c2", ""),
+            ("a3", "2 q"),
+        ];
+
+        assert_eq!(
+            signature_base_string(
+                "POST",
+                "http://example.com/request?b5=%3D%253D&a3=a&c%40=&a2=r%20b",
+                &params
+            ),
+            "POST&http%3A%2F%2Fexample.com%2Frequest&a2%3Dr%2520b%26a3%3D2%2520q\
+             %26a3%3Da%26b5%3D%253D%25253D%26c%2540%3D%26c2%3D%26oauth_consumer_\
+             key%3D9djdj82h48djs9d2%26oauth_nonce%3D7d8f3e4a%26oauth_signature_m\
+             ethod%3DHMAC-SHA1%26oauth_timestamp%3D137131201%26oauth_token%3Dkkk\
+             9d7dh3k39sjv7"
+        );
+    }
+
+    #[test]
+    fn rfc5849_base_string_uri() {
+        // RFC 5849 section 3.4.1.2
+        assert!(
+            signature_base_string("GET", "HTTP://EXAMPLE.COM:80/r%20v/X?id=123", &[])
+                .starts_with("GET&http%3A%2F%2Fexample.com%2Fr%2520v%2FX&")
+        );
+        assert!(
+            signature_base_string("GET", "https://www.example.net:8080/?q=1", &[])
+                .starts_with("GET&https%3A%2F%2Fwww.example.net%3A8080%2F&")
+        );
+    }
+
+    #[test]
+    fn twitter_base_string() {
+        let params = TWITTER_OAUTH
+            .iter()
+            .chain(TWITTER_QUERY)
+            .chain(TWITTER_FORM)
+            .copied()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            signature_base_string("post", TWITTER_URL, &params),
+            "POST&https%3A%2F%2Fapi.twitter.com%2F1.1%2Fstatuses%2Fupdate.json&inc\
+             lude_entities%3Dtrue%26oauth_consumer_key%3Dxvz1evFS4wEEPTGEFPHBog%2\
+             6oauth_nonce%3DkYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg%26oauth_si\
+             gnature_method%3DHMAC-SHA1%26oauth_timestamp%3D1318622958%26oauth_to\
+             ken%3D370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb%26oauth_ver\
+             sion%3D1.0%26status%3DHello%2520Ladies%2520%252B%2520Gentlemen%252C%\
+             2520a%2520signed%2520OAuth%2520request%2521"
+        );
+    }
+
+    #[test]
+    fn twitter_signature() {
+        let params = TWITTER_OAUTH
+            .iter()
+            .chain(TWITTER_QUERY)
+            .chain(TWITTER_FORM)
+            .copied()
+            .collect::<Vec<_>>();
+        let base_string = signature_base_string("POST", TWITTER_URL, &params);
+
+        assert_eq!(
+            signature(&base_string, TWITTER_SECRETS.0, TWITTER_SECRETS.1),
+            "hCtSmYh+iHYCEqBWrE7C7hYmtUk="
+        );
+    }
+
+    #[test]
+    fn twitter_header() {
+        let header = sign_header(
+            "POST",
+            TWITTER_URL,
+            TWITTER_OAUTH,
+            TWITTER_SECRETS,
+            Some(TWITTER_QUERY),
+            Some(TWITTER_FORM),
+        );
+
+        assert_eq!(
+            header,
+            "OAuth oauth_consumer_key=\"xvz1evFS4wEEPTGEFPHBog\",\
+             oauth_nonce=\"kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg\",\
+             oauth_signature=\"hCtSmYh%2BiHYCEqBWrE7C7hYmtUk%3D\",\
+             oauth_signature_method=\"HMAC-SHA1\",\
+             oauth_timestamp=\"1318622958\",\
+             oauth_token=\"370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb\",\
+             oauth_version=\"1.0\""
+        );
+    }
+
+    #[test]
+    fn percent_encoding() {
+        // RFC 5849 section 3.6, only unreserved characters are left as they are
+        assert_eq!(
+            percent_encode("Ladies + Gentlemen"),
+            "Ladies%20%2B%20Gentlemen"
+        );
+        assert_eq!(
+            percent_encode("An encoded string!"),
+            "An%20encoded%20string%21"
+        );
+        assert_eq!(
+            percent_encode("Dogs, Cats & Mice"),
+            "Dogs%2C%20Cats%20%26%20Mice"
+        );
+        assert_eq!(percent_encode("☃"), "%E2%98%83");
+        assert_eq!(percent_encode("-._~"), "-._~");
+    }
+}
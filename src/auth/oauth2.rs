@@ -0,0 +1,251 @@
+use super::{AuthHttp, TwitterAuth};
+use crate::error::Error;
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::Deserialize;
+
+use base64::{engine::general_purpose, Engine as _};
+use crypto::{digest::Digest, sha2::Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct TwitterBearer {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TwitterBearerResponse {
+    token_type: String,
+    access_token: String,
+}
+
+impl TwitterBearer {
+    pub async fn request(consumer_key: &str, consumer_secret: &str) -> Result<Self, Error> {
+        Self::request_with(&AuthHttp::default(), consumer_key, consumer_secret).await
+    }
+
+    pub async fn request_with(
+        http: &AuthHttp,
+        consumer_key: &str,
+        consumer_secret: &str,
+    ) -> Result<Self, Error> {
+        let res = http
+            .http
+            .post(format!("{}/oauth2/token", http.api_url))
+            .basic_auth(
+                urlencoding::encode(consumer_key),
+                Some(urlencoding::encode(consumer_secret)),
+            )
+            .form(&[("grant_type", "client_credentials")])
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(Error::BadCredentials);
+        }
+
+        match res.json::<TwitterBearerResponse>().await? {
+            TwitterBearerResponse {
+                token_type,
+                access_token,
+            } if token_type.eq_ignore_ascii_case("bearer") => Ok(Self {
+                token: access_token,
+            }),
+            _ => Err(Error::BadCredentials),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TwitterOAuth2 {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TwitterOAuth2TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+}
+
+impl TwitterOAuth2 {
+    async fn request_token(
+        http: &AuthHttp,
+        client_id: &str,
+        client_secret: Option<&str>,
+        form: &[(&str, &str)],
+    ) -> Result<TwitterOAuth2TokenResponse, Error> {
+        let mut req = http.http.post(format!("{}/2/oauth2/token", http.api_url));
+        if let Some(secret) = client_secret {
+            req = req.basic_auth(client_id, Some(secret));
+        }
+
+        let res = req.form(form).send().await?;
+        if !res.status().is_success() {
+            return Err(Error::BadCredentials);
+        }
+
+        Ok(res.json::<TwitterOAuth2TokenResponse>().await?)
+    }
+
+    fn from_response(
+        client_id: &str,
+        client_secret: Option<&str>,
+        res: TwitterOAuth2TokenResponse,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.map(Into::into),
+            access_token: res.access_token,
+            refresh_token: res.refresh_token,
+            expires_at: res.expires_in.map(|secs| unix_now() + secs),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| unix_now() >= at)
+    }
+
+    pub async fn refresh(&mut self) -> Result<(), Error> {
+        self.refresh_with(&AuthHttp::default()).await
+    }
+
+    pub async fn refresh_with(&mut self, http: &AuthHttp) -> Result<(), Error> {
+        let refresh_token = match &self.refresh_token {
+            Some(token) => token.clone(),
+            None => return Err(Error::BadCredentials),
+        };
+
+        let res = Self::request_token(
+            http,
+            &self.client_id,
+            self.client_secret.as_deref(),
+            &[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", &refresh_token),
+                ("client_id", &self.client_id),
+            ],
+        )
+        .await?;
+
+        // Twitter rotates refresh tokens, but keep the old one if none was returned.
+        let refresh_token = res.refresh_token.clone().or(Some(refresh_token));
+        *self = Self::from_response(&self.client_id, self.client_secret.as_deref(), res);
+        self.refresh_token = refresh_token;
+
+        Ok(())
+    }
+}
+
+/// OAuth 2.0 Authorization Code flow with PKCE.
+#[derive(Debug, Clone)]
+pub struct TwitterOAuth2Flow {
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    state: String,
+    code_verifier: String,
+    http: AuthHttp,
+}
+
+impl TwitterOAuth2Flow {
+    pub fn new(client_id: &str, redirect_uri: &str, scopes: &[&str]) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: None,
+            redirect_uri: redirect_uri.into(),
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+            state: random_string(32),
+            code_verifier: random_string(64),
+            http: AuthHttp::default(),
+        }
+    }
+
+    /// Only needed for confidential clients.
+    pub fn client_secret(&mut self, client_secret: &str) -> &mut Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+
+    /// Exchange the code with this HTTP client and api url instead of the defaults.
+    pub fn http(&mut self, http: AuthHttp) -> &mut Self {
+        self.http = http;
+        self
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    pub fn code_verifier(&self) -> &str {
+        &self.code_verifier
+    }
+
+    pub fn code_challenge(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.input_str(&self.code_verifier);
+        let mut digest = [0u8; 32];
+        hasher.result(&mut digest);
+
+        general_purpose::URL_SAFE_NO_PAD.encode(digest)
+    }
+
+    pub fn authorize_url(&self) -> String {
+        format!(
+            "https://twitter.com/i/oauth2/authorize?response_type=code&client_id={}&redirect_uri={}&scope={}&state={}&code_challenge={}&code_challenge_method=S256",
+            urlencoding::encode(&self.client_id),
+            urlencoding::encode(&self.redirect_uri),
+            urlencoding::encode(&self.scopes.join(" ")),
+            urlencoding::encode(&self.state),
+            self.code_challenge()
+        )
+    }
+
+    /// Exchanges the `code` from the authorization redirect for access and refresh tokens.
+    pub async fn exchange(&self, code: &str) -> Result<TwitterAuth, Error> {
+        let res = TwitterOAuth2::request_token(
+            &self.http,
+            &self.client_id,
+            self.client_secret.as_deref(),
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.redirect_uri),
+                ("code_verifier", &self.code_verifier),
+                ("client_id", &self.client_id),
+            ],
+        )
+        .await?;
+
+        Ok(TwitterAuth::from_oauth2(TwitterOAuth2::from_response(
+            &self.client_id,
+            self.client_secret.as_deref(),
+            res,
+        )))
+    }
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("unix failed??")
+        .as_secs()
+}
@@ -1,12 +1,205 @@
 use std::fmt;
 use std::error;
 
+use serde::Deserialize;
+
+use crate::ratelimit::RateLimit;
+use crate::TwitterPostData;
+
+/// A single entry of the `errors` array of a response. v1.1 errors only have `code`
+/// and `message`, v2 errors use the problem details fields.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TwitterApiResponseError {
+    code: Option<u32>,
+    message: Option<String>,
+    title: Option<String>,
+    detail: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    parameter: Option<String>,
+    resource_type: Option<String>,
+    resource_id: Option<String>,
+    value: Option<serde_json::Value>,
+}
+impl TwitterApiResponseError {
+    pub fn code(&self) -> Option<u32> {
+        self.code
+    }
+
+    /// `message` for v1.1 errors, `detail` for v2 errors.
+    pub fn message(&self) -> &str {
+        self.message
+            .as_deref()
+            .or(self.detail.as_deref())
+            .unwrap_or_default()
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn problem_type(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    pub fn parameter(&self) -> Option<&str> {
+        self.parameter.as_deref()
+    }
+
+    pub fn resource_type(&self) -> Option<&str> {
+        self.resource_type.as_deref()
+    }
+
+    pub fn resource_id(&self) -> Option<&str> {
+        self.resource_id.as_deref()
+    }
+
+    pub fn value(&self) -> Option<&serde_json::Value> {
+        self.value.as_ref()
+    }
+}
+
+/// An error response from the API: the HTTP status, the problem details of v2
+/// responses and the individual errors.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    status: u16,
+    title: Option<String>,
+    kind: Option<String>,
+    detail: Option<String>,
+    errors: Vec<TwitterApiResponseError>,
+    body: Option<Box<str>>,
+}
+impl ApiError {
+    pub(crate) fn new(
+        status: u16,
+        title: Option<String>,
+        kind: Option<String>,
+        detail: Option<String>,
+        errors: Vec<TwitterApiResponseError>,
+    ) -> Self {
+        Self {
+            status,
+            title,
+            kind,
+            detail,
+            errors,
+            body: None,
+        }
+    }
+
+    /// An error response that isn't JSON, e.g. an HTML page of a proxy or load balancer.
+    pub(crate) fn from_body(status: u16, body: String) -> Self {
+        Self {
+            status,
+            title: None,
+            kind: None,
+            detail: None,
+            errors: Vec::new(),
+            body: Some(body.into()),
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The problem `type`, e.g. `https://api.twitter.com/2/problems/resource-not-found`.
+    pub fn problem_type(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn errors(&self) -> &[TwitterApiResponseError] {
+        &self.errors
+    }
+
+    /// The raw body of a response that couldn't be parsed as JSON.
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    fn has_problem(&self, problem: &str) -> bool {
+        self.problem_type()
+            .is_some_and(|kind| kind.ends_with(problem))
+            || self.errors.iter().any(|err| {
+                err.problem_type()
+                    .is_some_and(|kind| kind.ends_with(problem))
+            })
+    }
+
+    fn has_code(&self, codes: &[u32]) -> bool {
+        self.errors
+            .iter()
+            .any(|err| err.code().is_some_and(|code| codes.contains(&code)))
+    }
+
+    pub fn is_duplicate_content(&self) -> bool {
+        self.has_code(&[187])
+            || self
+                .detail()
+                .is_some_and(|detail| detail.contains("duplicate content"))
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status == 404
+            || self.has_problem("resource-not-found")
+            || self.has_code(&[34, 50, 144])
+    }
+
+    pub fn is_forbidden(&self) -> bool {
+        self.status == 403 || self.has_problem("not-authorized-for-resource")
+    }
+
+    pub fn is_unauthorized(&self) -> bool {
+        self.status == 401
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.status)?;
+        if let Some(title) = &self.title {
+            write!(f, " {}", title)?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, ": {}", detail)?;
+        }
+        for err in &self.errors {
+            write!(f, "; {}", err.message())?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     Reqwest(reqwest::Error),
+    Io(std::io::Error),
+    Api(ApiError),
+    /// The response couldn't be parsed, with the raw body that was received.
+    Deserialize(serde_json::Error, String),
+    InvalidUrl(String),
+    MissingHeader(&'static str),
+    UnknownMediaType,
+    /// Every rule the tweet breaks, found before sending it.
+    InvalidTweet(Vec<String>),
+    /// Every problem with a search query, found before sending it.
+    InvalidQuery(Vec<String>),
+    /// Posting a thread failed, with the tweets that were posted before the error.
+    Thread(Vec<TwitterPostData>, Box<Error>),
     BadMedia,
+    BadCredentials,
+    AuthorizationDenied,
     NoUserData,
-    TooManyRequests,
+    TooManyRequests(Option<RateLimit>),
     Unknown
 }
 
@@ -15,9 +208,33 @@ impl fmt::Display for Error {
         match *self {
             Error::Reqwest(ref err) => write!(f, "HTTP request error: {}", err),
             Error::BadMedia => write!(f, "faulty media"),
+            Error::Io(ref err) => write!(f, "IO error: {}", err),
+            Error::Api(ref err) => write!(f, "API error: {}", err),
+            Error::Deserialize(ref err, _) => write!(f, "unexpected response: {}", err),
+            Error::InvalidUrl(ref url) => write!(f, "invalid url: {}", url),
+            Error::MissingHeader(header) => write!(f, "missing {} header", header),
+            Error::UnknownMediaType => write!(f, "unknown media type"),
+            Error::InvalidTweet(ref problems) => {
+                write!(f, "invalid tweet: {}", problems.join("; "))
+            }
+            Error::InvalidQuery(ref problems) => {
+                write!(f, "invalid query: {}", problems.join("; "))
+            }
+            Error::Thread(ref posted, ref err) => {
+                write!(f, "thread stopped after {} tweets: {}", posted.len(), err)
+            }
+            Error::BadCredentials => write!(f, "invalid credentials"),
+            Error::AuthorizationDenied => write!(f, "authorization denied"),
             Error::NoUserData => write!(f, "No user data found"),
             Error::Unknown => write!(f, "unknown"),
-            Error::TooManyRequests => write!(f, "too many reqs"),
+            Error::TooManyRequests(Some(ref limit)) => {
+                write!(
+                    f,
+                    "too many reqs, resets in {}s",
+                    limit.reset_in().as_secs()
+                )
+            }
+            Error::TooManyRequests(None) => write!(f, "too many reqs"),
         }
     }
 }
@@ -27,13 +244,30 @@ impl error::Error for Error {
         match *self {
             Error::Reqwest(ref err) => Some(err),
             Error::BadMedia => None,
+            Error::Io(ref err) => Some(err),
+            Error::Api(_) => None,
+            Error::Deserialize(ref err, _) => Some(err),
+            Error::InvalidUrl(_) => None,
+            Error::MissingHeader(_) => None,
+            Error::UnknownMediaType => None,
+            Error::InvalidTweet(_) => None,
+            Error::InvalidQuery(_) => None,
+            Error::Thread(_, ref err) => Some(err.as_ref()),
+            Error::BadCredentials => None,
+            Error::AuthorizationDenied => None,
             Error::NoUserData => None,
             Error::Unknown => None,
-            Error::TooManyRequests => None,
+            Error::TooManyRequests(_) => None,
         }
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Error {
         Error::Reqwest(err)
@@ -0,0 +1,166 @@
+//! The `tweet.fields`, `user.fields`, `media.fields`, `place.fields`, `poll.fields`
+//! and `expansions` query parameters.
+
+use std::collections::HashMap;
+use std::fmt;
+
+macro_rules! fields {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant),*
+        }
+        impl $name {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $value),*
+                }
+            }
+        }
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+fields!(TweetField {
+    Attachments => "attachments",
+    AuthorId => "author_id",
+    ContextAnnotations => "context_annotations",
+    ConversationId => "conversation_id",
+    CreatedAt => "created_at",
+    EditControls => "edit_controls",
+    EditHistoryTweetIds => "edit_history_tweet_ids",
+    Entities => "entities",
+    Geo => "geo",
+    Id => "id",
+    InReplyToUserId => "in_reply_to_user_id",
+    Lang => "lang",
+    PossiblySensitive => "possibly_sensitive",
+    PublicMetrics => "public_metrics",
+    ReferencedTweets => "referenced_tweets",
+    ReplySettings => "reply_settings",
+    Source => "source",
+    Text => "text",
+    Withheld => "withheld",
+});
+
+fields!(UserField {
+    CreatedAt => "created_at",
+    Description => "description",
+    Entities => "entities",
+    Id => "id",
+    Location => "location",
+    Name => "name",
+    PinnedTweetId => "pinned_tweet_id",
+    ProfileImageUrl => "profile_image_url",
+    Protected => "protected",
+    PublicMetrics => "public_metrics",
+    Url => "url",
+    Username => "username",
+    Verified => "verified",
+    VerifiedType => "verified_type",
+    Withheld => "withheld",
+});
+
+fields!(MediaField {
+    AltText => "alt_text",
+    DurationMs => "duration_ms",
+    Height => "height",
+    MediaKey => "media_key",
+    PreviewImageUrl => "preview_image_url",
+    PublicMetrics => "public_metrics",
+    Type => "type",
+    Url => "url",
+    Variants => "variants",
+    Width => "width",
+});
+
+fields!(PlaceField {
+    ContainedWithin => "contained_within",
+    Country => "country",
+    CountryCode => "country_code",
+    FullName => "full_name",
+    Geo => "geo",
+    Id => "id",
+    Name => "name",
+    PlaceType => "place_type",
+});
+
+fields!(PollField {
+    DurationMinutes => "duration_minutes",
+    EndDatetime => "end_datetime",
+    Id => "id",
+    Options => "options",
+    VotingStatus => "voting_status",
+});
+
+fields!(
+    /// Objects referenced by the result that should be returned in `includes`.
+    Expansion {
+        AttachmentsMediaKeys => "attachments.media_keys",
+        AttachmentsPollIds => "attachments.poll_ids",
+        AuthorId => "author_id",
+        EditHistoryTweetIds => "edit_history_tweet_ids",
+        EntitiesMentionsUsername => "entities.mentions.username",
+        GeoPlaceId => "geo.place_id",
+        InReplyToUserId => "in_reply_to_user_id",
+        PinnedTweetId => "pinned_tweet_id",
+        ReferencedTweetsId => "referenced_tweets.id",
+        ReferencedTweetsIdAuthorId => "referenced_tweets.id.author_id",
+    }
+);
+
+pub(crate) fn join<T: fmt::Display>(fields: &[T]) -> String {
+    fields
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Which fields and expansions to request, turned into query parameters.
+#[derive(Debug, Clone, Default)]
+pub struct FieldsBuilder(pub HashMap<&'static str, String>);
+impl FieldsBuilder {
+    pub fn tweet(&mut self, fields: &[TweetField]) -> &mut Self {
+        self.0.insert("tweet.fields", join(fields));
+        self
+    }
+
+    pub fn user(&mut self, fields: &[UserField]) -> &mut Self {
+        self.0.insert("user.fields", join(fields));
+        self
+    }
+
+    pub fn media(&mut self, fields: &[MediaField]) -> &mut Self {
+        self.0.insert("media.fields", join(fields));
+        self
+    }
+
+    pub fn place(&mut self, fields: &[PlaceField]) -> &mut Self {
+        self.0.insert("place.fields", join(fields));
+        self
+    }
+
+    pub fn poll(&mut self, fields: &[PollField]) -> &mut Self {
+        self.0.insert("poll.fields", join(fields));
+        self
+    }
+
+    pub fn expansions(&mut self, expansions: &[Expansion]) -> &mut Self {
+        self.0.insert("expansions", join(expansions));
+        self
+    }
+
+    pub(crate) fn query(&self) -> Vec<(&str, &str)> {
+        self.0
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect()
+    }
+}
@@ -1,7 +1,8 @@
 #![allow(unused)]
 
+use reqwest::header::AUTHORIZATION;
 use reqwest::multipart::Form;
-use reqwest::{header::AUTHORIZATION, Client};
+use reqwest::{Client, Proxy, RequestBuilder, Response, StatusCode};
 use reqwest::{Method, Url};
 use serde::de::DeserializeOwned;
 
@@ -9,18 +10,13 @@ use std::{
     collections::HashMap,
     env, fs,
     str::FromStr,
-    time::{SystemTime, UNIX_EPOCH},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-#[derive(Debug, Deserialize)]
-struct TwitterApiResponseError {
-    code: Option<u32>,
-    message: String,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct TwitterPostData {
     id: String,
@@ -42,6 +38,11 @@ impl TwitterPostData {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct TwitterDeleteData {
+    deleted: bool,
+}
+
 #[derive(Debug, Deserialize)]
 struct TwitterPost {
     data: Option<TwitterPostData>,
@@ -53,30 +54,33 @@ use auth::*;
 
 pub struct TweetMediaBuilder(pub HashMap<&'static str, Value>);
 impl TweetMediaBuilder {
+    fn push_id(&mut self, id: String) {
+        match self
+            .0
+            .entry("media_ids")
+            .or_insert_with(|| Value::from(Vec::<Value>::new()))
+        {
+            Value::Array(medias) => medias.push(Value::String(id)),
+            other => *other = Value::from(vec![Value::String(id)]),
+        }
+    }
+
     pub fn add(&mut self, media: Option<TwitterMediaResponse>) -> &mut Self {
         if let Some(data) = media {
-            let medias = self
-                .0
-                .entry("media_ids")
-                .or_insert_with(|| Value::from(Vec::<Value>::new()))
-                .as_array_mut()
-                .expect("no media_ids???");
-
-            medias.push(Value::from(data.media_id_string.as_str()));
+            self.push_id(data.media_id_string);
         }
 
         self
     }
 
     pub fn id(&mut self, id: u64) -> &mut Self {
-        let medias = self
-            .0
-            .entry("media_ids")
-            .or_insert_with(|| Value::from(Vec::<Value>::new()))
-            .as_array_mut()
-            .expect("no media_ids???");
+        self.push_id(id.to_string());
+        self
+    }
 
-        medias.push(Value::String(id.to_string()));
+    /// Users tagged in the attached images.
+    pub fn tagged_user_ids(&mut self, user_ids: &[&str]) -> &mut Self {
+        self.0.insert("tagged_user_ids", json!(user_ids));
         self
     }
 }
@@ -89,6 +93,17 @@ impl Default for TweetMediaBuilder {
     }
 }
 
+/// Who can reply to a tweet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplySettings {
+    Everyone,
+    MentionedUsers,
+    Following,
+    Subscribers,
+    Verified,
+}
+
 #[derive(Default)]
 pub struct TweetBuilder(pub HashMap<&'static str, Value>);
 impl TweetBuilder {
@@ -97,6 +112,156 @@ impl TweetBuilder {
         self
     }
 
+    /// Makes the tweet a reply. Users in `exclude_reply_user_ids` aren't mentioned
+    /// in the reply even though they are part of the conversation.
+    pub fn reply(
+        &mut self,
+        in_reply_to_tweet_id: &str,
+        exclude_reply_user_ids: &[&str],
+    ) -> &mut Self {
+        let mut reply = json!({ "in_reply_to_tweet_id": in_reply_to_tweet_id });
+        if !exclude_reply_user_ids.is_empty() {
+            reply["exclude_reply_user_ids"] = json!(exclude_reply_user_ids);
+        }
+
+        self.0.insert("reply", reply);
+        self
+    }
+
+    pub fn quote(&mut self, tweet_id: &str) -> &mut Self {
+        self.0.insert("quote_tweet_id", Value::from(tweet_id));
+        self
+    }
+
+    pub fn poll(&mut self, options: &[&str], duration_minutes: u32) -> &mut Self {
+        self.0.insert(
+            "poll",
+            json!({ "options": options, "duration_minutes": duration_minutes }),
+        );
+        self
+    }
+
+    pub fn reply_settings(&mut self, settings: ReplySettings) -> &mut Self {
+        match settings {
+            // everyone is the default, the api doesn't take it as a value
+            ReplySettings::Everyone => self.0.remove("reply_settings"),
+            settings => self.0.insert("reply_settings", json!(settings)),
+        };
+        self
+    }
+
+    pub fn geo(&mut self, place_id: &str) -> &mut Self {
+        self.0.insert("geo", json!({ "place_id": place_id }));
+        self
+    }
+
+    pub fn for_super_followers_only(&mut self) -> &mut Self {
+        self.0.insert("for_super_followers_only", Value::from(true));
+        self
+    }
+
+    pub fn direct_message_deep_link(&mut self, link: &str) -> &mut Self {
+        self.0.insert("direct_message_deep_link", Value::from(link));
+        self
+    }
+
+    /// Weighted characters left in the text, negative if it is too long.
+    pub fn remaining_chars(&self) -> i64 {
+        let text = self
+            .0
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        text::parse(text).remaining()
+    }
+
+    /// Checks the rules Twitter would reject the tweet for, listing every one broken.
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut problems = Vec::new();
+
+        let text = self
+            .0
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let media = self.0.get("media");
+        let media_ids = media
+            .and_then(|media| media.get("media_ids"))
+            .and_then(Value::as_array)
+            .map_or(0, Vec::len);
+        let poll = self.0.get("poll");
+        let quote = self.0.contains_key("quote_tweet_id");
+
+        if text.trim().is_empty() && media_ids == 0 {
+            problems.push("a tweet needs text or media".to_string());
+        }
+        if self.remaining_chars() < 0 {
+            problems.push(format!(
+                "text is {} characters too long",
+                -self.remaining_chars()
+            ));
+        }
+        if media_ids > 4 {
+            problems.push(format!(
+                "at most 4 media can be attached, got {}",
+                media_ids
+            ));
+        }
+        // e.g. every `add` got `None` because the uploads failed
+        if media.is_some() && media_ids == 0 {
+            problems.push("media was added without any media ids".to_string());
+        }
+        if media_ids > 0 && poll.is_some() {
+            problems.push("a tweet can't have both media and a poll".to_string());
+        }
+        if media_ids > 0 && quote {
+            problems.push("a quote tweet can't have media".to_string());
+        }
+        if quote && poll.is_some() {
+            problems.push("a quote tweet can't have a poll".to_string());
+        }
+
+        if let Some(poll) = poll {
+            let options = poll
+                .get("options")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            if !(2..=4).contains(&options.len()) {
+                problems.push(format!(
+                    "a poll needs 2 to 4 options, got {}",
+                    options.len()
+                ));
+            }
+            for option in options.iter().filter_map(Value::as_str) {
+                if !(1..=25).contains(&option.chars().count()) {
+                    problems.push(format!(
+                        "poll option \"{}\" has to be 1 to 25 characters long",
+                        option
+                    ));
+                }
+            }
+
+            let duration = poll
+                .get("duration_minutes")
+                .and_then(Value::as_u64)
+                .unwrap_or_default();
+            if !(5..=10080).contains(&duration) {
+                problems.push(format!(
+                    "a poll has to last 5 to 10080 minutes, got {}",
+                    duration
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidTweet(problems))
+        }
+    }
+
     /*pub fn add_media(&mut self, media: &str) -> &mut Self {
         self.0.insert(
             "media",
@@ -117,249 +282,753 @@ impl TweetBuilder {
     }
 }
 
+pub mod text;
+
+pub mod fields;
+use fields::{FieldsBuilder, UserField};
+
+pub mod tweet;
+use tweet::{Entities, Includes, Tweet, Withheld};
+
+pub mod paginate;
+use paginate::Paginator;
+
+pub mod search;
+use search::{Counts, CountsBuilder, SearchBuilder};
+
+pub mod query;
+
+pub mod timeline;
+use timeline::TimelineBuilder;
+
+pub mod ratelimit;
+use ratelimit::RateLimit;
+
+pub mod retry;
+use retry::RetryPolicy;
+
 pub mod error;
-use error::Error;
+use error::{ApiError, Error, TwitterApiResponseError};
 
 #[derive(Debug, Deserialize)]
 struct TwitterApiResponse {
+    title: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
     detail: Option<String>,
     errors: Option<Vec<TwitterApiResponseError>>,
     data: Option<Value>,
+    includes: Option<Includes>,
+    meta: Option<Meta>,
+}
+impl TwitterApiResponse {
+    fn into_error(self, status: StatusCode) -> Error {
+        match self.errors {
+            None if self.title.is_none() && self.detail.is_none() && status.is_success() => {
+                Error::Unknown
+            }
+            errors => Error::Api(ApiError::new(
+                status.as_u16(),
+                self.title,
+                self.kind,
+                self.detail,
+                errors.unwrap_or_default(),
+            )),
+        }
+    }
+
+    fn into_data<T: DeserializeOwned>(self, status: StatusCode) -> Result<T, Error> {
+        match self.data {
+            Some(data) => {
+                T::deserialize(&data).map_err(|err| Error::Deserialize(err, data.to_string()))
+            }
+            None => Err(self.into_error(status)),
+        }
+    }
+
+    fn into_response<T: DeserializeOwned>(
+        mut self,
+        status: StatusCode,
+    ) -> Result<TwitterResponse<T>, Error> {
+        // lists come back without `data` when nothing was found
+        let empty = status
+            .is_success()
+            .then(|| T::deserialize(&json!([])).ok())
+            .flatten();
+        let data = match (self.data.take(), empty) {
+            (Some(data), _) => {
+                T::deserialize(&data).map_err(|err| Error::Deserialize(err, data.to_string()))?
+            }
+            (None, Some(empty)) => empty,
+            (None, None) => return Err(self.into_error(status)),
+        };
+
+        Ok(TwitterResponse {
+            data,
+            includes: self.includes.unwrap_or_default(),
+            errors: self.errors.unwrap_or_default(),
+            meta: self.meta.unwrap_or_default(),
+        })
+    }
+}
+
+/// The data of a response together with the objects in `includes` and the errors
+/// about parts of the request that failed, e.g. ids of deleted tweets.
+#[derive(Debug, Clone)]
+pub struct TwitterResponse<T> {
+    data: T,
+    includes: Includes,
+    errors: Vec<TwitterApiResponseError>,
+    meta: Meta,
+}
+impl<T> TwitterResponse<T> {
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn into_data(self) -> T {
+        self.data
+    }
+
+    pub fn includes(&self) -> &Includes {
+        &self.includes
+    }
+
+    pub fn errors(&self) -> &[TwitterApiResponseError] {
+        &self.errors
+    }
+
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+}
+
+/// Information about a page of results.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Meta {
+    result_count: Option<u64>,
+    next_token: Option<String>,
+    previous_token: Option<String>,
+    newest_id: Option<String>,
+    oldest_id: Option<String>,
+    total_tweet_count: Option<u64>,
+}
+impl Meta {
+    pub fn result_count(&self) -> Option<u64> {
+        self.result_count
+    }
+
+    /// Token of the next page, `None` on the last one.
+    pub fn next_token(&self) -> Option<&str> {
+        self.next_token.as_deref()
+    }
+
+    pub fn previous_token(&self) -> Option<&str> {
+        self.previous_token.as_deref()
+    }
+
+    pub fn newest_id(&self) -> Option<&str> {
+        self.newest_id.as_deref()
+    }
+
+    pub fn oldest_id(&self) -> Option<&str> {
+        self.oldest_id.as_deref()
+    }
+
+    /// Sum of the counts of a page of [`TweetCount`]s.
+    pub fn total_tweet_count(&self) -> Option<u64> {
+        self.total_tweet_count
+    }
+}
+
+pub struct TwitterClientBuilder {
+    auth: Arc<dyn Authenticator>,
+    api_url: String,
+    upload_url: String,
+    user_agent: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<Proxy>,
+    http: Option<Client>,
+    retry: Option<RetryPolicy>,
+}
+impl TwitterClientBuilder {
+    pub fn new<A: Authenticator + 'static>(auth: A) -> Self {
+        Self {
+            auth: Arc::new(auth),
+            api_url: "https://api.twitter.com".into(),
+            upload_url: "https://upload.twitter.com".into(),
+            user_agent: "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.4 Safari/605.1.15".into(),
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            http: None,
+            retry: None,
+        }
+    }
+
+    /// Base url of the v2 api, `https://api.twitter.com` by default.
+    pub fn api_url(&mut self, url: &str) -> &mut Self {
+        self.api_url = url.trim_end_matches('/').into();
+        self
+    }
+
+    /// Base url of media uploads, `https://upload.twitter.com` by default.
+    pub fn upload_url(&mut self, url: &str) -> &mut Self {
+        self.upload_url = url.trim_end_matches('/').into();
+        self
+    }
+
+    pub fn user_agent(&mut self, user_agent: &str) -> &mut Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Timeout for a whole request, from connecting until the body is read.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// An HTTP(S) or SOCKS5 proxy, e.g. `reqwest::Proxy::all("socks5://127.0.0.1:9050")`.
+    pub fn proxy(&mut self, proxy: Proxy) -> &mut Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Use an already configured `reqwest::Client`. The user agent, timeouts and
+    /// proxy of this builder are ignored then.
+    pub fn http_client(&mut self, http: Client) -> &mut Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// See [`TwitterClient::retry`].
+    pub fn retry<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut RetryPolicy) -> &mut RetryPolicy,
+    {
+        let mut retry = RetryPolicy::default();
+        f(&mut retry);
+
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn build(&self) -> Result<TwitterClient, Error> {
+        let http = match &self.http {
+            Some(http) => http.clone(),
+            None => {
+                let mut builder = Client::builder().user_agent(&self.user_agent);
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(proxy) = &self.proxy {
+                    builder = builder.proxy(proxy.clone());
+                }
+                builder.build()?
+            }
+        };
+
+        Ok(TwitterClient {
+            http,
+            auth: self.auth.clone(),
+            api_url: self.api_url.clone(),
+            upload_url: self.upload_url.clone(),
+            rate_limits: Arc::default(),
+            retry: self.retry.clone(),
+            my_id: Arc::default(),
+        })
+    }
+}
+
+// numeric path segments after the version are ids, e.g. `/2/users/42/tweets` is
+// `/2/users/:id/tweets`
+fn endpoint_template(path: &str) -> String {
+    path.split('/')
+        .enumerate()
+        .map(|(i, segment)| {
+            if i > 1 && !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()) {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+async fn parse_body<T: DeserializeOwned>(res: Response) -> Result<T, Error> {
+    let status = res.status();
+    let body = res.text().await?;
+
+    serde_json::from_str(&body).map_err(|err| {
+        // e.g. a 502 page from the edge, keep the status so it can be told apart
+        if status.is_success() {
+            Error::Deserialize(err, body)
+        } else {
+            Error::Api(ApiError::from_body(status.as_u16(), body))
+        }
+    })
 }
 
 #[derive(Clone)]
 pub struct TwitterClient {
     http: Client,
-    auth: TwitterAuth,
+    auth: Arc<dyn Authenticator>,
+    api_url: String,
+    upload_url: String,
+    rate_limits: Arc<Mutex<HashMap<String, RateLimit>>>,
+    retry: Option<RetryPolicy>,
+    // id of the authenticated user, once `me` has been called
+    my_id: Arc<Mutex<Option<String>>>,
 }
 impl TwitterClient {
     pub fn new(auth: TwitterAuth) -> Result<Self, Box<dyn std::error::Error>> {
-        let http = Client::builder()
-            .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/16.4 Safari/605.1.15")
-            .build()?;
+        Self::with_authenticator(auth)
+    }
+
+    /// Creates a client that authenticates its requests with a custom [`Authenticator`].
+    pub fn with_authenticator<A: Authenticator + 'static>(
+        auth: A,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(TwitterClientBuilder::new(auth).build()?)
+    }
+
+    pub fn builder<A: Authenticator + 'static>(auth: A) -> TwitterClientBuilder {
+        TwitterClientBuilder::new(auth)
+    }
+
+    /// Opt in to retrying requests that fail with `429` or `5xx`, configured
+    /// starting from [`RetryPolicy::default`].
+    pub fn retry<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut RetryPolicy) -> &mut RetryPolicy,
+    {
+        let mut retry = RetryPolicy::default();
+        f(&mut retry);
+
+        self.retry = Some(retry);
+        self
+    }
+
+    /// The HTTP client and api url of this client, to send token requests
+    /// (e.g. [`TwitterOAuth2Flow::http`]) through the same proxy and timeouts.
+    pub fn auth_http(&self) -> AuthHttp {
+        AuthHttp {
+            http: self.http.clone(),
+            api_url: self.api_url.clone(),
+        }
+    }
 
-        Ok(Self { http, auth })
+    /// The rate limit state of an endpoint as of its last response, e.g.
+    /// `client.rate_limit("/2/users/:id/tweets")` or `client.rate_limit("POST /2/tweets")`.
+    /// Without a method `GET` is assumed. Numeric ids in the path, e.g.
+    /// `/2/users/42/tweets`, are read as `:id`.
+    pub fn rate_limit(&self, endpoint: &str) -> Option<RateLimit> {
+        let (method, path) = match endpoint.split_once(' ') {
+            Some((method, path)) => (method.to_ascii_uppercase(), path),
+            None => ("GET".to_string(), endpoint),
+        };
+        let path = Url::parse(path).map_or(path.to_string(), |url| url.path().to_string());
+
+        self.rate_limits
+            .lock()
+            .expect("poisoned rate limit lock")
+            .get(&format!("{} {}", method, endpoint_template(&path)))
+            .copied()
+    }
+
+    // `endpoint` is the route, e.g. `/2/tweets/:id`, rate limits are kept per route
+    async fn _send<F>(
+        &self,
+        method: &str,
+        endpoint: &str,
+        url: &str,
+        query: Option<&[(&str, &str)]>,
+        form: Option<&[(&str, &str)]>,
+        body: F,
+    ) -> Result<Response, Error>
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let info = AuthRequest {
+            method,
+            url,
+            query,
+            form,
+        };
+
+        let http_method = Method::from_str(method).unwrap_or(Method::GET);
+
+        let mut refreshed = false;
+        let mut attempt = 0;
+        loop {
+            let req = self.http.request(
+                http_method.clone(),
+                Url::parse_with_params(url, query.unwrap_or_default())
+                    .map_err(|_| Error::InvalidUrl(url.into()))?,
+            );
+            let req = body(self.auth.authenticate(req, &info)?).build()?;
+            let authorization = req
+                .headers()
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from);
+            let res = self.http.execute(req).await?;
+
+            let rate_limit = RateLimit::from_headers(res.headers());
+            if let Some(rate_limit) = rate_limit {
+                self.rate_limits
+                    .lock()
+                    .expect("poisoned rate limit lock")
+                    .insert(format!("{} {}", method, endpoint), rate_limit);
+            }
+
+            let delay = self.retry.as_ref().and_then(|retry| {
+                retry.delay(
+                    attempt,
+                    &http_method,
+                    res.status(),
+                    res.headers(),
+                    rate_limit,
+                )
+            });
+            if let Some(delay) = delay {
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+
+            if res.status() == StatusCode::TOO_MANY_REQUESTS {
+                return Err(Error::TooManyRequests(rate_limit));
+            }
+
+            // e.g. OAuth 2.0 user context tokens expire, get a new one and try again
+            if res.status() == StatusCode::UNAUTHORIZED
+                && !refreshed
+                && self
+                    .auth
+                    .refresh(&self.auth_http(), authorization.as_deref())
+                    .await?
+            {
+                refreshed = true;
+                continue;
+            }
+
+            return Ok(res);
+        }
     }
 
     async fn _request_t<T: DeserializeOwned>(
-        &mut self,
+        &self,
         method: &str,
+        endpoint: &str,
         url: &str,
         query: Option<&[(&str, &str)]>,
     ) -> Result<T, Error> {
-        Ok(self
-            .http
-            .request(
-                Method::from_str(method).unwrap_or(Method::GET),
-                Url::parse_with_params(url, query.unwrap_or_default()).unwrap(),
-            )
-            .header(AUTHORIZATION, &self.auth.header(method, url, query))
-            .send()
-            .await?
-            .json::<T>()
-            .await?)
+        parse_body(
+            self._send(method, endpoint, url, query, None, |req| req)
+                .await?,
+        )
+        .await
     }
 
     async fn _request<T: DeserializeOwned>(
-        &mut self,
+        &self,
         method: &str,
+        endpoint: &str,
         url: &str,
         query: Option<&[(&str, &str)]>,
     ) -> Result<T, Error> {
         let res = self
-            .http
-            .request(
-                Method::from_str(method).unwrap_or(Method::GET),
-                Url::parse_with_params(url, query.unwrap_or_default()).unwrap(),
-            )
-            .header(AUTHORIZATION, &self.auth.header(method, url, query))
-            .send()
+            ._send(method, endpoint, url, query, None, |req| req)
+            .await?;
+        let status = res.status();
+
+        parse_body::<TwitterApiResponse>(res)
             .await?
-            .json::<TwitterApiResponse>()
+            .into_data(status)
+    }
+
+    async fn _request_response<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        endpoint: &str,
+        url: &str,
+        query: Option<&[(&str, &str)]>,
+    ) -> Result<TwitterResponse<T>, Error> {
+        let res = self
+            ._send(method, endpoint, url, query, None, |req| req)
             .await?;
+        let status = res.status();
 
-        match res.data {
-            Some(data) => Ok(serde_json::from_value(data).unwrap()),
-            None => {
-                if let Some(detail) = res.detail {
-                    match detail.as_ref() {
-                        "Too Many Requests" => Err(Error::TooManyRequests),
-                        _ => Err(Error::Unknown),
-                    }
-                } else if let Some(errors) = res.errors {
-                    println!("got errors: {:?}", errors);
-                    Err(Error::Unknown)
-                } else {
-                    Err(Error::Unknown)
-                }
-            }
+        parse_body::<TwitterApiResponse>(res)
+            .await?
+            .into_response(status)
+    }
+
+    // looks up `values` 100 at a time, merging the responses
+    async fn _request_batched<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        url: &str,
+        key: &str,
+        values: &[&str],
+        fields: &FieldsBuilder,
+    ) -> Result<TwitterResponse<Vec<T>>, Error> {
+        let mut merged = TwitterResponse {
+            data: Vec::new(),
+            includes: Includes::default(),
+            errors: Vec::new(),
+            meta: Meta::default(),
+        };
+        for chunk in values.chunks(100) {
+            let values = chunk.join(",");
+            let mut query = fields.query();
+            query.push((key, &values));
+
+            let res = self
+                ._request_response::<Vec<T>>("GET", endpoint, url, Some(&query))
+                .await?;
+            merged.data.extend(res.data);
+            merged.includes.extend(res.includes);
+            merged.errors.extend(res.errors);
         }
+
+        Ok(merged)
     }
 
     async fn _json_request<T: DeserializeOwned>(
-        &mut self,
+        &self,
         method: &str,
+        endpoint: &str,
         url: &str,
         json: Value,
         query: Option<&[(&str, &str)]>,
     ) -> Result<T, Error> {
         let res = self
-            .http
-            .request(
-                Method::from_str(method).unwrap_or(Method::GET),
-                Url::parse_with_params(url, query.unwrap_or_default()).unwrap(),
-            )
-            .header(AUTHORIZATION, &self.auth.header(method, url, query))
-            .json(&json)
-            .send()
-            .await?
-            .json::<TwitterApiResponse>()
+            ._send(method, endpoint, url, query, None, |req| req.json(&json))
             .await?;
+        let status = res.status();
 
-        match res.data {
-            Some(data) => Ok(serde_json::from_value(data).unwrap()),
-            None => {
-                if let Some(detail) = res.detail {
-                    match detail.as_ref() {
-                        "Too Many Requests" => Err(Error::TooManyRequests),
-                        _ => Err(Error::Unknown),
-                    }
-                } else if let Some(errors) = res.errors {
-                    println!("got errors: {:?}", errors);
-                    Err(Error::Unknown)
-                } else {
-                    Err(Error::Unknown)
-                }
-            }
-        }
+        parse_body::<TwitterApiResponse>(res)
+            .await?
+            .into_data(status)
     }
 
     async fn _multipart_request<T: DeserializeOwned>(
-        &mut self,
+        &self,
         method: &str,
+        endpoint: &str,
         url: &str,
-        multipart: Form,
+        multipart: impl Fn() -> Form,
         query: Option<&[(&str, &str)]>,
     ) -> Result<T, Error> {
         let res = self
-            .http
-            .request(
-                Method::from_str(method).unwrap_or(Method::GET),
-                Url::parse_with_params(url, query.unwrap_or_default()).unwrap(),
-            )
-            .header(AUTHORIZATION, &self.auth.header(method, url, query))
-            .multipart(multipart)
-            .send()
-            .await?
-            .json::<Value>()
+            ._send(method, endpoint, url, query, None, |req| {
+                req.multipart(multipart())
+            })
             .await?;
+        let status = res.status();
+        let res = parse_body::<Value>(res).await?;
 
-        if let Some(errors) = res.get("errors") {
-            println!("got errors: {:?}", errors);
-            return Err(Error::Unknown);
+        if !status.is_success() || res.get("errors").is_some() {
+            return Err(serde_json::from_value::<TwitterApiResponse>(res)
+                .map_or(Error::Unknown, |res| res.into_error(status)));
         }
 
-        match serde_json::from_value::<T>(res) {
-            Ok(data) => Ok(data),
-            Err(_) => Err(Error::BadMedia),
-        }
+        let body = res.to_string();
+        serde_json::from_value::<T>(res).map_err(|err| Error::Deserialize(err, body))
+    }
+
+    /// Pages through any v2 list endpoint that takes a `pagination_token`, e.g.
+    /// `client.paginate::<TwitterUserData>("/2/users/2244994945/followers")`.
+    pub fn paginate<T: DeserializeOwned + Send + 'static>(&self, path: &str) -> Paginator<T> {
+        Paginator::new(
+            self.clone(),
+            endpoint_template(path),
+            format!("{}{}", self.api_url, path),
+            "pagination_token",
+        )
+    }
+
+    pub async fn me(&self, fields: Option<&[UserField]>) -> Result<TwitterUserData, Error> {
+        let fields_str = fields.map(fields::join).filter(|fields| !fields.is_empty());
+        let query = fields_str
+            .as_deref()
+            .map(|fields| [("user.fields", fields)]);
+
+        let url = format!("{}/2/users/me", self.api_url);
+
+        let me: TwitterUserData = self
+            ._request("GET", "/2/users/me", &url, query.as_ref().map(|q| &q[..]))
+            .await?;
+        *self.my_id.lock().expect("poisoned user id lock") = Some(me.id().to_string());
+
+        Ok(me)
+    }
+
+    pub async fn user_by_id<F>(
+        &self,
+        id: &str,
+        f: F,
+    ) -> Result<TwitterResponse<TwitterUserData>, Error>
+    where
+        F: FnOnce(&mut FieldsBuilder) -> &mut FieldsBuilder,
+    {
+        let mut fields = FieldsBuilder::default();
+        f(&mut fields);
+
+        let url = format!("{}/2/users/{}", self.api_url, id);
+
+        self._request_response("GET", "/2/users/:id", &url, Some(&fields.query()))
+            .await
+    }
+
+    /// `username` is without the `@`.
+    pub async fn user_by_username<F>(
+        &self,
+        username: &str,
+        f: F,
+    ) -> Result<TwitterResponse<TwitterUserData>, Error>
+    where
+        F: FnOnce(&mut FieldsBuilder) -> &mut FieldsBuilder,
+    {
+        let mut fields = FieldsBuilder::default();
+        f(&mut fields);
+
+        let url = format!("{}/2/users/by/username/{}", self.api_url, username);
+
+        self._request_response(
+            "GET",
+            "/2/users/by/username/:username",
+            &url,
+            Some(&fields.query()),
+        )
+        .await
+    }
+
+    /// Looks up any number of users, 100 per request. Users that couldn't be
+    /// found (suspended, deleted, ...) are in [`TwitterResponse::errors`] instead.
+    pub async fn users_by_ids<F>(
+        &self,
+        ids: &[&str],
+        f: F,
+    ) -> Result<TwitterResponse<Vec<TwitterUserData>>, Error>
+    where
+        F: FnOnce(&mut FieldsBuilder) -> &mut FieldsBuilder,
+    {
+        let mut fields = FieldsBuilder::default();
+        f(&mut fields);
+
+        let url = format!("{}/2/users", self.api_url);
+
+        self._request_batched("/2/users", &url, "ids", ids, &fields)
+            .await
     }
 
-    pub async fn me(&mut self, fields: Option<&[&str]>) -> Result<TwitterUserData, Error> {
-        let fields_str = fields.map_or(String::new(), |f| f.join(","));
-        let query = [("user.fields", fields_str.as_str())];
+    /// Like [`TwitterClient::users_by_ids`], by username.
+    pub async fn users_by_usernames<F>(
+        &self,
+        usernames: &[&str],
+        f: F,
+    ) -> Result<TwitterResponse<Vec<TwitterUserData>>, Error>
+    where
+        F: FnOnce(&mut FieldsBuilder) -> &mut FieldsBuilder,
+    {
+        let mut fields = FieldsBuilder::default();
+        f(&mut fields);
+
+        let url = format!("{}/2/users/by", self.api_url);
 
-        self._request("GET", "https://api.twitter.com/2/users/me", Some(&query))
+        self._request_batched("/2/users/by", &url, "usernames", usernames, &fields)
             .await
     }
 
     pub async fn upload_media(
-        &mut self,
+        &self,
         path: &str,
         filename: Option<String>,
     ) -> Result<TwitterMediaResponse, Error> {
+        const UPLOAD: &str = "/1.1/media/upload.json";
+        let upload_url = format!("{}{}", self.upload_url, UPLOAD);
+
         let file_bytes;
         let mime;
         if path.starts_with("http") {
-            let media = reqwest::get(path).await?;
-            let headers = media.headers().clone();
-            let content_type_header = headers
+            let media = self.http.get(path).send().await?;
+            let content_type_header = media
+                .headers()
                 .get(reqwest::header::CONTENT_TYPE)
-                .unwrap()
-                .to_str()
-                .unwrap()
+                .and_then(|content_type| content_type.to_str().ok())
+                .ok_or(Error::MissingHeader("content-type"))?
                 .to_owned();
             file_bytes = media.bytes().await?.to_vec();
             mime = content_type_header;
         } else {
-            match fs::read(path) {
-                Ok(bytes) => {
-                    file_bytes = bytes;
-                    mime = infer::get(&file_bytes).unwrap().mime_type().to_string();
-                }
-                _ => return Err(Error::BadMedia),
-            }
+            file_bytes = fs::read(path)?;
+            mime = infer::get(&file_bytes)
+                .ok_or(Error::UnknownMediaType)?
+                .mime_type()
+                .to_string();
         }
 
         let mut chunked = false;
         let len = file_bytes.len();
         if len <= 1024 * 1024 {
             // simple upload
-            let file_part = reqwest::multipart::Part::bytes(file_bytes)
-                .file_name(filename.unwrap_or("media".into()));
-            let form = reqwest::multipart::Form::new().part("media", file_part);
-
-            self._multipart_request(
-                "POST",
-                "https://upload.twitter.com/1.1/media/upload.json",
-                form,
-                None,
-            )
-            .await
+            let filename = filename.unwrap_or("media".into());
+            let form = || {
+                let file_part =
+                    reqwest::multipart::Part::bytes(file_bytes.clone()).file_name(filename.clone());
+                reqwest::multipart::Form::new().part("media", file_part)
+            };
+
+            self._multipart_request("POST", UPLOAD, &upload_url, form, None)
+                .await
         } else {
             // chunked media upload
             chunked = true;
-            let init = self
+            let media_id = self
                 ._multipart_request::<TwitterMediaResponse>(
                     "POST",
-                    "https://upload.twitter.com/1.1/media/upload.json",
-                    reqwest::multipart::Form::new()
-                        .text("command", "INIT")
-                        .text("total_bytes", len.to_string())
-                        .text("media_type", mime.clone()),
+                    UPLOAD,
+                    &upload_url,
+                    || {
+                        reqwest::multipart::Form::new()
+                            .text("command", "INIT")
+                            .text("total_bytes", len.to_string())
+                            .text("media_type", mime.clone())
+                    },
                     None,
                 )
-                .await;
-
-            let media_id = match init {
-                Ok(data) => data.media_id_string,
-                _ => return Err(Error::BadMedia),
-            };
+                .await?
+                .media_id_string;
 
             for (i, chunk) in file_bytes.chunks(1024 * 1024).enumerate() {
                 let append = self
-                    .http
-                    .post("https://upload.twitter.com/1.1/media/upload.json")
-                    .header(
-                        AUTHORIZATION,
-                        &self.auth.header(
-                            "POST",
-                            "https://upload.twitter.com/1.1/media/upload.json",
-                            None,
-                        ),
-                    )
-                    .multipart(
-                        reqwest::multipart::Form::new()
-                            .text("command", "APPEND")
-                            .text("media_id", media_id.to_string())
-                            .text("segment_index", i.to_string())
-                            .part(
-                                "media",
-                                reqwest::multipart::Part::bytes(chunk.to_vec())
-                                    .file_name(format!("media_chunk_{}", i)),
-                            ),
-                    )
-                    .send()
+                    ._send("POST", UPLOAD, &upload_url, None, None, |req| {
+                        req.multipart(
+                            reqwest::multipart::Form::new()
+                                .text("command", "APPEND")
+                                .text("media_id", media_id.to_string())
+                                .text("segment_index", i.to_string())
+                                .part(
+                                    "media",
+                                    reqwest::multipart::Part::bytes(chunk.to_vec())
+                                        .file_name(format!("media_chunk_{}", i)),
+                                ),
+                        )
+                    })
                     .await?
                     .status();
 
@@ -368,18 +1037,22 @@ impl TwitterClient {
                 }
             }
 
-            let mut finalize_form = reqwest::multipart::Form::new()
-                .text("command", "FINALIZE")
-                .text("media_id", media_id.to_string());
+            let finalize_form = || {
+                let mut form = reqwest::multipart::Form::new()
+                    .text("command", "FINALIZE")
+                    .text("media_id", media_id.to_string());
 
-            if chunked {
-                finalize_form = finalize_form.text("allow_async", "true");
-            }
+                if chunked {
+                    form = form.text("allow_async", "true");
+                }
+                form
+            };
 
             let finalize = self
                 ._multipart_request::<TwitterMediaResponse>(
                     "POST",
-                    "https://upload.twitter.com/1.1/media/upload.json",
+                    UPLOAD,
+                    &upload_url,
                     finalize_form,
                     None,
                 )
@@ -387,27 +1060,25 @@ impl TwitterClient {
 
             if finalize.processing_info.is_some() {
                 loop {
-                    let status = self
+                    let mut data = self
                         ._request_t::<TwitterMediaResponse>(
                             "GET",
-                            "https://upload.twitter.com/1.1/media/upload.json",
+                            UPLOAD,
+                            &upload_url,
                             Some(&[("command", "STATUS"), ("media_id", &media_id)]),
                         )
-                        .await;
-
-                    match status {
-                        Ok(mut data) => match data.status() {
-                            MediaStatus::InProgress => {
-                                //println!("in progress");
-                                tokio::time::sleep(tokio::time::Duration::from_secs(
-                                    data.seconds_left(),
-                                ))
-                                .await;
-                                continue;
-                            }
-                            MediaStatus::Succeeded => return Ok(data),
-                            _ => return Err(Error::BadMedia),
-                        },
+                        .await?;
+
+                    match data.status() {
+                        MediaStatus::InProgress => {
+                            //println!("in progress");
+                            tokio::time::sleep(tokio::time::Duration::from_secs(
+                                data.seconds_left(),
+                            ))
+                            .await;
+                            continue;
+                        }
+                        MediaStatus::Succeeded => return Ok(data),
                         _ => return Err(Error::BadMedia),
                     }
                 }
@@ -417,20 +1088,249 @@ impl TwitterClient {
         }
     }
 
-    pub async fn tweet<F>(&mut self, f: F) -> Result<TwitterPostData, Error>
+    pub async fn tweet<F>(&self, f: F) -> Result<TwitterPostData, Error>
     where
         F: FnOnce(&mut TweetBuilder) -> &mut TweetBuilder,
     {
         let mut tweet = TweetBuilder::default();
         f(&mut tweet);
+        tweet.validate()?;
+
+        let url = format!("{}/2/tweets", self.api_url);
+
+        self._json_request("POST", "/2/tweets", &url, json!(tweet.0), None)
+            .await
+    }
+
+    /// Posts the tweets as a thread, each one replying to the one before it. Every
+    /// tweet is validated before the first one is posted. If posting stops halfway,
+    /// [`Error::Thread`] has the tweets that did get posted.
+    pub async fn thread<I>(&self, tweets: I) -> Result<Vec<TwitterPostData>, Error>
+    where
+        I: IntoIterator,
+        I::Item: FnOnce(&mut TweetBuilder) -> &mut TweetBuilder,
+    {
+        let mut builders = Vec::new();
+        for f in tweets {
+            let mut tweet = TweetBuilder::default();
+            f(&mut tweet);
+            tweet.validate()?;
+
+            builders.push(tweet);
+        }
+
+        let url = format!("{}/2/tweets", self.api_url);
+
+        let mut posted: Vec<TwitterPostData> = Vec::new();
+        for mut tweet in builders {
+            if let Some(previous) = posted.last() {
+                tweet.reply(previous.id(), &[]);
+            }
+
+            match self
+                ._json_request("POST", "/2/tweets", &url, json!(tweet.0), None)
+                .await
+            {
+                Ok(data) => posted.push(data),
+                Err(err) => return Err(Error::Thread(posted, Box::new(err))),
+            }
+        }
+
+        Ok(posted)
+    }
+
+    /// Splits the text into tweets between sentences (see [`text::split`]) and posts
+    /// them as a thread.
+    pub async fn thread_text(&self, text: &str) -> Result<Vec<TwitterPostData>, Error> {
+        fn part(text: &str) -> impl FnOnce(&mut TweetBuilder) -> &mut TweetBuilder + '_ {
+            move |tweet| tweet.text(text)
+        }
+
+        let parts = text::split(text, text::MAX_WEIGHTED_LENGTH);
+
+        self.thread(parts.iter().map(|text| part(text))).await
+    }
+
+    pub async fn get_tweet<F>(&self, id: &str, f: F) -> Result<TwitterResponse<Tweet>, Error>
+    where
+        F: FnOnce(&mut FieldsBuilder) -> &mut FieldsBuilder,
+    {
+        let mut fields = FieldsBuilder::default();
+        f(&mut fields);
+
+        let url = format!("{}/2/tweets/{}", self.api_url, id);
+
+        self._request_response("GET", "/2/tweets/:id", &url, Some(&fields.query()))
+            .await
+    }
+
+    /// Looks up any number of tweets, 100 per request. Tweets that couldn't be
+    /// found (deleted, protected, ...) are in [`TwitterResponse::errors`] instead.
+    pub async fn get_tweets<F>(
+        &self,
+        ids: &[&str],
+        f: F,
+    ) -> Result<TwitterResponse<Vec<Tweet>>, Error>
+    where
+        F: FnOnce(&mut FieldsBuilder) -> &mut FieldsBuilder,
+    {
+        let mut fields = FieldsBuilder::default();
+        f(&mut fields);
+
+        let url = format!("{}/2/tweets", self.api_url);
+
+        self._request_batched("/2/tweets", &url, "ids", ids, &fields)
+            .await
+    }
+
+    /// Tweets of the last 7 days matching `query`, use [`Paginator::fields`] for more
+    /// than the default fields.
+    pub fn search_recent<F>(&self, query: &str, f: F) -> Paginator<Tweet>
+    where
+        F: FnOnce(&mut SearchBuilder) -> &mut SearchBuilder,
+    {
+        let mut search = SearchBuilder::default();
+        f(&mut search);
+
+        self._search("/2/tweets/search/recent", query, &search.0)
+    }
+
+    /// Like [`TwitterClient::search_recent`], over every tweet since 2006. Needs
+    /// Pro or Academic Research access.
+    pub fn search_all<F>(&self, query: &str, f: F) -> Paginator<Tweet>
+    where
+        F: FnOnce(&mut SearchBuilder) -> &mut SearchBuilder,
+    {
+        let mut search = SearchBuilder::default();
+        f(&mut search);
+
+        self._search("/2/tweets/search/all", query, &search.0)
+    }
 
-        self._json_request(
-            "POST",
-            "https://api.twitter.com/2/tweets",
-            json!(tweet.0),
-            None,
+    /// How many tweets of the last 7 days matched `query`, per minute, hour or day.
+    pub fn count_recent<F>(&self, query: &str, f: F) -> Counts
+    where
+        F: FnOnce(&mut CountsBuilder) -> &mut CountsBuilder,
+    {
+        let mut counts = CountsBuilder::default();
+        f(&mut counts);
+
+        Counts::new(self._search("/2/tweets/counts/recent", query, &counts.0))
+    }
+
+    /// Like [`TwitterClient::count_recent`], over every tweet since 2006. Needs
+    /// Pro or Academic Research access.
+    pub fn count_all<F>(&self, query: &str, f: F) -> Counts
+    where
+        F: FnOnce(&mut CountsBuilder) -> &mut CountsBuilder,
+    {
+        let mut counts = CountsBuilder::default();
+        f(&mut counts);
+
+        Counts::new(self._search("/2/tweets/counts/all", query, &counts.0))
+    }
+
+    fn _search<T: DeserializeOwned + Send + 'static>(
+        &self,
+        path: &str,
+        query: &str,
+        params: &HashMap<&'static str, String>,
+    ) -> Paginator<T> {
+        // search and counts take `next_token` rather than `pagination_token`
+        let mut paginator = Paginator::new(
+            self.clone(),
+            path.into(),
+            format!("{}{}", self.api_url, path),
+            "next_token",
+        );
+        paginator.query("query", query);
+        for (key, value) in params {
+            paginator.query(key, value);
+        }
+
+        paginator
+    }
+
+    /// Tweets posted by the user, newest first. Only the last 3200 can be reached.
+    pub fn user_tweets<F>(&self, user_id: &str, f: F) -> Paginator<Tweet>
+    where
+        F: FnOnce(&mut TimelineBuilder) -> &mut TimelineBuilder,
+    {
+        let mut timeline = TimelineBuilder::default();
+        f(&mut timeline);
+
+        self._timeline(
+            "/2/users/:id/tweets",
+            &format!("/2/users/{}/tweets", user_id),
+            &timeline.0,
         )
-        .await
+    }
+
+    /// Tweets mentioning the user, newest first. Only the last 800 can be reached.
+    pub fn user_mentions<F>(&self, user_id: &str, f: F) -> Paginator<Tweet>
+    where
+        F: FnOnce(&mut TimelineBuilder) -> &mut TimelineBuilder,
+    {
+        let mut timeline = TimelineBuilder::default();
+        f(&mut timeline);
+        timeline.0.remove("exclude");
+
+        self._timeline(
+            "/2/users/:id/mentions",
+            &format!("/2/users/{}/mentions", user_id),
+            &timeline.0,
+        )
+    }
+
+    /// Tweets of the authenticated user and the accounts they follow, newest first.
+    /// Needs user context authentication. The user's id is looked up with [`me`](Self::me)
+    /// on the first call and reused after that.
+    pub async fn home_timeline<F>(&self, f: F) -> Result<Paginator<Tweet>, Error>
+    where
+        F: FnOnce(&mut TimelineBuilder) -> &mut TimelineBuilder,
+    {
+        let mut timeline = TimelineBuilder::default();
+        f(&mut timeline);
+
+        let cached = self.my_id.lock().expect("poisoned user id lock").clone();
+        let my_id = match cached {
+            Some(id) => id,
+            None => self.me(None).await?.id,
+        };
+
+        Ok(self._timeline(
+            "/2/users/:id/timelines/reverse_chronological",
+            &format!("/2/users/{}/timelines/reverse_chronological", my_id),
+            &timeline.0,
+        ))
+    }
+
+    fn _timeline(
+        &self,
+        endpoint: &str,
+        path: &str,
+        params: &HashMap<&'static str, String>,
+    ) -> Paginator<Tweet> {
+        let mut paginator = Paginator::new(
+            self.clone(),
+            endpoint.into(),
+            format!("{}{}", self.api_url, path),
+            "pagination_token",
+        );
+        for (key, value) in params {
+            paginator.query(key, value);
+        }
+
+        paginator
+    }
+
+    /// Deletes one of the authenticated user's tweets, `true` if it got deleted.
+    pub async fn delete_tweet(&self, id: &str) -> Result<bool, Error> {
+        let url = format!("{}/2/tweets/{}", self.api_url, id);
+
+        self._request::<TwitterDeleteData>("DELETE", "/2/tweets/:id", &url, None)
+            .await
+            .map(|data| data.deleted)
     }
 }
 
@@ -471,8 +1371,7 @@ impl TwitterMediaResponse {
     pub fn seconds_left(&mut self) -> u64 {
         self.processing_info
             .as_ref()
-            .unwrap()
-            .check_after_secs
+            .and_then(|processing_info| processing_info.check_after_secs)
             .unwrap_or(1)
     }
 
@@ -481,13 +1380,23 @@ impl TwitterMediaResponse {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct TwitterUserData {
     id: String,
     name: String,
     username: String,
     description: Option<String>,
     created_at: Option<String>,
+    profile_image_url: Option<String>,
+    verified: Option<bool>,
+    verified_type: Option<String>,
+    protected: Option<bool>,
+    location: Option<String>,
+    url: Option<String>,
+    pinned_tweet_id: Option<String>,
+    public_metrics: Option<UserPublicMetrics>,
+    entities: Option<UserEntities>,
+    withheld: Option<Withheld>,
 }
 impl TwitterUserData {
     pub fn id(&self) -> &str {
@@ -521,6 +1430,95 @@ impl TwitterUserData {
             None => "invalid",
         }
     }
+
+    pub fn profile_image_url(&self) -> Option<&str> {
+        self.profile_image_url.as_deref()
+    }
+
+    pub fn verified(&self) -> Option<bool> {
+        self.verified
+    }
+
+    /// `blue`, `business`, `government` or `none`.
+    pub fn verified_type(&self) -> Option<&str> {
+        self.verified_type.as_deref()
+    }
+
+    pub fn protected(&self) -> Option<bool> {
+        self.protected
+    }
+
+    /// As typed by the user, not necessarily a real place.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    pub fn pinned_tweet_id(&self) -> Option<&str> {
+        self.pinned_tweet_id.as_deref()
+    }
+
+    pub fn public_metrics(&self) -> Option<&UserPublicMetrics> {
+        self.public_metrics.as_ref()
+    }
+
+    pub fn entities(&self) -> Option<&UserEntities> {
+        self.entities.as_ref()
+    }
+
+    pub fn withheld(&self) -> Option<&Withheld> {
+        self.withheld.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct UserPublicMetrics {
+    followers_count: u64,
+    following_count: u64,
+    tweet_count: u64,
+    listed_count: u64,
+    #[serde(default)]
+    like_count: u64,
+}
+impl UserPublicMetrics {
+    pub fn followers_count(&self) -> u64 {
+        self.followers_count
+    }
+
+    pub fn following_count(&self) -> u64 {
+        self.following_count
+    }
+
+    pub fn tweet_count(&self) -> u64 {
+        self.tweet_count
+    }
+
+    pub fn listed_count(&self) -> u64 {
+        self.listed_count
+    }
+
+    pub fn like_count(&self) -> u64 {
+        self.like_count
+    }
+}
+
+/// Entities of the profile url and of the description.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UserEntities {
+    url: Option<Entities>,
+    description: Option<Entities>,
+}
+impl UserEntities {
+    pub fn url(&self) -> Option<&Entities> {
+        self.url.as_ref()
+    }
+
+    pub fn description(&self) -> Option<&Entities> {
+        self.description.as_ref()
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -528,3 +1526,92 @@ struct TwitterUserResponse {
     detail: Option<String>,
     data: Option<TwitterUserData>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems<F>(f: F) -> Vec<String>
+    where
+        F: FnOnce(&mut TweetBuilder) -> &mut TweetBuilder,
+    {
+        let mut tweet = TweetBuilder::default();
+        f(&mut tweet);
+
+        match tweet.validate() {
+            Ok(()) => Vec::new(),
+            Err(Error::InvalidTweet(problems)) => problems,
+            Err(err) => panic!("unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn valid_tweets() {
+        assert!(problems(|t| t.text("hello")).is_empty());
+        assert!(problems(|t| t.media(|m| m.id(1).id(2))).is_empty());
+        assert!(problems(|t| t.text("which?").poll(&["a", "b"], 60)).is_empty());
+        assert!(problems(|t| t.text("look").quote("1")).is_empty());
+    }
+
+    #[test]
+    fn text_and_media() {
+        assert_eq!(problems(|t| t), vec!["a tweet needs text or media"]);
+        assert_eq!(
+            problems(|t| t.text("  ")),
+            vec!["a tweet needs text or media"]
+        );
+        assert_eq!(
+            problems(|t| t.text(&"a".repeat(281))),
+            vec!["text is 1 characters too long"]
+        );
+        assert_eq!(
+            problems(|t| t.text("pics").media(|m| m.add(None))),
+            vec!["media was added without any media ids"]
+        );
+        assert_eq!(
+            problems(|t| t.text("pics").media(|m| m.tagged_user_ids(&["1"]))),
+            vec!["media was added without any media ids"]
+        );
+        assert_eq!(
+            problems(|t| t.media(|m| m.id(1).id(2).id(3).id(4).id(5))),
+            vec!["at most 4 media can be attached, got 5"]
+        );
+    }
+
+    #[test]
+    fn exclusive_attachments() {
+        assert_eq!(
+            problems(|t| t.media(|m| m.id(1)).poll(&["a", "b"], 60)),
+            vec!["a tweet can't have both media and a poll"]
+        );
+        assert_eq!(
+            problems(|t| t.media(|m| m.id(1)).quote("1")),
+            vec!["a quote tweet can't have media"]
+        );
+        assert_eq!(
+            problems(|t| t.text("which?").quote("1").poll(&["a", "b"], 60)),
+            vec!["a quote tweet can't have a poll"]
+        );
+    }
+
+    #[test]
+    fn polls() {
+        assert_eq!(
+            problems(|t| t.text("which?").poll(&["a"], 60)),
+            vec!["a poll needs 2 to 4 options, got 1"]
+        );
+        assert_eq!(
+            problems(|t| t.text("which?").poll(&["a", &"b".repeat(26)], 60)),
+            vec![format!(
+                "poll option \"{}\" has to be 1 to 25 characters long",
+                "b".repeat(26)
+            )]
+        );
+        assert_eq!(
+            problems(|t| t.text("which?").poll(&["a", "b"], 4)),
+            vec!["a poll has to last 5 to 10080 minutes, got 4"]
+        );
+        // every problem is reported, not just the first
+        assert_eq!(problems(|t| t.poll(&[""], 10081)).len(), 4);
+    }
+}
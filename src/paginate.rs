@@ -0,0 +1,160 @@
+//! Walking through the pages of v2 list endpoints.
+
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+use crate::error::Error;
+use crate::fields::FieldsBuilder;
+use crate::{TwitterClient, TwitterResponse};
+
+/// Requests pages of a list endpoint one after another, following `meta.next_token`.
+/// Nothing is requested until the stream from [`Paginator::pages`] or
+/// [`Paginator::items`] is polled.
+pub struct Paginator<T> {
+    client: TwitterClient,
+    endpoint: String,
+    url: String,
+    query: Vec<(String, String)>,
+    token_param: &'static str,
+    max_results: Option<u32>,
+    limit: Option<usize>,
+    token: Option<String>,
+    item: PhantomData<fn() -> T>,
+}
+// derived Clone would require `T: Clone`
+impl<T> Clone for Paginator<T> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            endpoint: self.endpoint.clone(),
+            url: self.url.clone(),
+            query: self.query.clone(),
+            token_param: self.token_param,
+            max_results: self.max_results,
+            limit: self.limit,
+            token: self.token.clone(),
+            item: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned + Send + 'static> Paginator<T> {
+    pub(crate) fn new(
+        client: TwitterClient,
+        endpoint: String,
+        url: String,
+        token_param: &'static str,
+    ) -> Self {
+        Self {
+            client,
+            endpoint,
+            url,
+            query: Vec::new(),
+            token_param,
+            max_results: None,
+            limit: None,
+            token: None,
+            item: PhantomData,
+        }
+    }
+
+    pub fn query(&mut self, key: &str, value: &str) -> &mut Self {
+        self.query.retain(|(k, _)| k != key);
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn fields<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut FieldsBuilder) -> &mut FieldsBuilder,
+    {
+        let mut fields = FieldsBuilder::default();
+        f(&mut fields);
+
+        for (key, value) in fields.query() {
+            self.query(key, value);
+        }
+        self
+    }
+
+    /// Items per page. Each endpoint has its own range, e.g. 10 to 100 for
+    /// recent search.
+    pub fn max_results(&mut self, max_results: u32) -> &mut Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Stop after this many items in total, the last page is cut short if needed.
+    pub fn limit(&mut self, limit: usize) -> &mut Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Start from a token saved from [`Meta::next_token`](crate::Meta::next_token)
+    /// instead of the first page.
+    pub fn resume(&mut self, token: &str) -> &mut Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    async fn fetch(&self, token: Option<&str>) -> Result<TwitterResponse<Vec<T>>, Error> {
+        let max_results = self.max_results.map(|max| max.to_string());
+
+        let mut query = self
+            .query
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect::<Vec<_>>();
+        if let Some(max_results) = &max_results {
+            query.push(("max_results", max_results));
+        }
+        if let Some(token) = token {
+            query.push((self.token_param, token));
+        }
+
+        self.client
+            ._request_response("GET", &self.endpoint, &self.url, Some(&query))
+            .await
+    }
+
+    /// Every page, in order. The stream ends after the last page, after the
+    /// [`limit`](Paginator::limit) is reached or after the first error.
+    pub fn pages(&self) -> impl Stream<Item = Result<TwitterResponse<Vec<T>>, Error>> + Send {
+        let paginator = self.clone();
+        let token = paginator.token.clone();
+
+        stream::unfold(Some((paginator, token, 0)), |state| async move {
+            let (paginator, token, count) = state?;
+
+            let mut page = match paginator.fetch(token.as_deref()).await {
+                Ok(page) => page,
+                Err(err) => return Some((Err(err), None)),
+            };
+
+            let count = count + page.data.len();
+            if let Some(limit) = paginator.limit {
+                page.data
+                    .truncate(page.data.len() - count.saturating_sub(limit));
+            }
+
+            // a page can be empty and still have a next one, e.g. when everything on it
+            // was filtered out, so only the token and the limit decide whether to go on
+            let next = match page.meta().next_token() {
+                Some(token) if paginator.limit.is_none_or(|limit| count < limit) => {
+                    Some((paginator, Some(token.to_string()), count))
+                }
+                _ => None,
+            };
+
+            Some((Ok(page), next))
+        })
+    }
+
+    /// The items of every page, see [`Paginator::pages`].
+    pub fn items(&self) -> impl Stream<Item = Result<T, Error>> + Send {
+        self.pages()
+            .map_ok(|page| stream::iter(page.into_data().into_iter().map(Ok)))
+            .try_flatten()
+    }
+}
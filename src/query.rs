@@ -0,0 +1,440 @@
+//! Composing search and filtered stream queries, checked for the mistakes that
+//! make Twitter reject a query or silently match nothing.
+
+use crate::error::Error;
+
+/// Decides how long a query can be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessTier {
+    /// Free, Basic, Essential and Elevated access, up to 512 characters.
+    Basic,
+    /// Pro and Academic Research access, up to 1024 characters.
+    Pro,
+}
+impl AccessTier {
+    pub fn max_length(&self) -> usize {
+        match self {
+            AccessTier::Basic => 512,
+            AccessTier::Pro => 1024,
+        }
+    }
+}
+
+/// `is:` operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Is {
+    Retweet,
+    Reply,
+    Quote,
+    Verified,
+    Nullcast,
+}
+impl Is {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Is::Retweet => "retweet",
+            Is::Reply => "reply",
+            Is::Quote => "quote",
+            Is::Verified => "verified",
+            Is::Nullcast => "nullcast",
+        }
+    }
+}
+
+/// `has:` operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Has {
+    Media,
+    Images,
+    Videos,
+    Links,
+    Hashtags,
+    Cashtags,
+    Mentions,
+    Geo,
+}
+impl Has {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Has::Media => "media",
+            Has::Images => "images",
+            Has::Videos => "videos",
+            Has::Links => "links",
+            Has::Hashtags => "hashtags",
+            Has::Cashtags => "cashtags",
+            Has::Mentions => "mentions",
+            Has::Geo => "geo",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Clause {
+    // standalone operators can make up a query by themselves, the others (`is:`,
+    // `has:`, `lang:`, ...) only narrow one down
+    Term {
+        text: String,
+        standalone: bool,
+    },
+    // `name` includes its prefix character or colon, e.g. `from:` or `#`
+    Operator {
+        name: &'static str,
+        value: String,
+        quoted: bool,
+        standalone: bool,
+    },
+    Not(Box<Clause>),
+    Group(Vec<Clause>),
+    Or(Vec<Clause>),
+}
+impl Clause {
+    fn standalone(&self) -> bool {
+        match self {
+            Clause::Term { standalone, .. } | Clause::Operator { standalone, .. } => *standalone,
+            Clause::Not(_) => false,
+            Clause::Group(clauses) => clauses.iter().any(Clause::standalone),
+            Clause::Or(clauses) => !clauses.is_empty() && clauses.iter().all(Clause::standalone),
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            Clause::Term { text, .. } => text.clone(),
+            Clause::Operator {
+                name,
+                value,
+                quoted: true,
+                ..
+            } => format!("{}\"{}\"", name, value),
+            Clause::Operator { name, value, .. } => format!("{}{}", name, value),
+            Clause::Not(clause) => format!("-{}", clause.render()),
+            Clause::Group(clauses) if clauses.len() == 1 => clauses[0].render(),
+            Clause::Group(clauses) => format!("({})", render_all(clauses, " ")),
+            Clause::Or(clauses) if clauses.len() == 1 => clauses[0].render(),
+            Clause::Or(clauses) => format!("({})", render_all(clauses, " OR ")),
+        }
+    }
+
+    fn problems(&self, negated: bool, problems: &mut Vec<String>) {
+        match self {
+            Clause::Term { text, .. }
+                if text.ends_with(':')
+                    || text.ends_with(":\"\"")
+                    || matches!(text.as_str(), "" | "#" | "@" | "$" | "\"\"") =>
+            {
+                problems.push(format!("`{}` is missing its value", text));
+            }
+            Clause::Term { text, .. } if matches!(text.as_str(), "OR" | "AND") => {
+                problems.push(format!(
+                    "a bare `{}` is read as an operator, use a phrase to search for the word",
+                    text
+                ));
+            }
+            Clause::Term { .. } => {}
+            Clause::Operator { name, value, .. } if value.is_empty() => {
+                problems.push(format!("`{}` is missing its value", name));
+            }
+            Clause::Operator { name, value, .. }
+                if value.contains(|c: char| c.is_whitespace() || c == '"') =>
+            {
+                problems.push(format!(
+                    "`{}` values can't contain whitespace or quotes, got `{}`",
+                    name, value
+                ));
+            }
+            Clause::Operator { name, value, .. }
+                if *name == "is:" && value == Is::Nullcast.as_str() && !negated =>
+            {
+                problems.push("`is:nullcast` can only be used negated".to_string());
+            }
+            Clause::Operator { .. } => {}
+            Clause::Not(clause) if matches!(**clause, Clause::Group(_) | Clause::Or(_)) => {
+                problems.push(format!(
+                    "`-{}` negates a group, negate each clause in it instead",
+                    clause.render()
+                ));
+            }
+            Clause::Not(clause) => clause.problems(true, problems),
+            Clause::Group(clauses) | Clause::Or(clauses) if clauses.is_empty() => {
+                problems.push("groups can't be empty".to_string());
+            }
+            Clause::Group(clauses) | Clause::Or(clauses) => {
+                for clause in clauses {
+                    clause.problems(negated, problems);
+                }
+            }
+        }
+    }
+}
+
+fn render_all(clauses: &[Clause], separator: &str) -> String {
+    clauses
+        .iter()
+        .map(Clause::render)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Clauses are ANDed together, use [`QueryBuilder::any`] for OR.
+#[derive(Debug, Clone, Default)]
+pub struct QueryBuilder {
+    clauses: Vec<Clause>,
+}
+impl QueryBuilder {
+    fn term(&mut self, text: String, standalone: bool) -> &mut Self {
+        self.clauses.push(Clause::Term { text, standalone });
+        self
+    }
+
+    fn operator(&mut self, name: &'static str, value: &str, standalone: bool) -> &mut Self {
+        self.clauses.push(Clause::Operator {
+            name,
+            value: value.into(),
+            quoted: false,
+            standalone,
+        });
+        self
+    }
+
+    /// Matches the word anywhere in the text, a keyword with spaces or quotes is
+    /// matched as an exact phrase.
+    pub fn keyword(&mut self, keyword: &str) -> &mut Self {
+        if keyword.contains(|c: char| c.is_whitespace() || c == '"') {
+            return self.phrase(keyword);
+        }
+
+        self.term(keyword.into(), true)
+    }
+
+    pub fn phrase(&mut self, phrase: &str) -> &mut Self {
+        self.term(format!("\"{}\"", phrase.replace('"', "\\\"")), true)
+    }
+
+    pub fn from(&mut self, username: &str) -> &mut Self {
+        self.operator("from:", username.trim_start_matches('@'), true)
+    }
+
+    pub fn to(&mut self, username: &str) -> &mut Self {
+        self.operator("to:", username.trim_start_matches('@'), true)
+    }
+
+    pub fn mention(&mut self, username: &str) -> &mut Self {
+        self.operator("@", username.trim_start_matches('@'), true)
+    }
+
+    pub fn retweets_of(&mut self, username: &str) -> &mut Self {
+        self.operator("retweets_of:", username.trim_start_matches('@'), true)
+    }
+
+    pub fn hashtag(&mut self, tag: &str) -> &mut Self {
+        self.operator("#", tag.trim_start_matches('#'), true)
+    }
+
+    pub fn cashtag(&mut self, tag: &str) -> &mut Self {
+        self.operator("$", tag.trim_start_matches('$'), true)
+    }
+
+    pub fn url(&mut self, url: &str) -> &mut Self {
+        self.clauses.push(Clause::Operator {
+            name: "url:",
+            value: url.into(),
+            quoted: true,
+            standalone: true,
+        });
+        self
+    }
+
+    pub fn conversation_id(&mut self, id: &str) -> &mut Self {
+        self.operator("conversation_id:", id, true)
+    }
+
+    /// A BCP 47 language code like `en`.
+    pub fn lang(&mut self, lang: &str) -> &mut Self {
+        self.operator("lang:", lang, false)
+    }
+
+    /// An ISO 3166-1 alpha-2 country code like `US`.
+    pub fn place_country(&mut self, country_code: &str) -> &mut Self {
+        self.operator("place_country:", country_code, false)
+    }
+
+    /// `Is::Nullcast` only works negated, inside [`QueryBuilder::not`].
+    pub fn is(&mut self, is: Is) -> &mut Self {
+        self.operator("is:", is.as_str(), false)
+    }
+
+    pub fn has(&mut self, has: Has) -> &mut Self {
+        self.operator("has:", has.as_str(), false)
+    }
+
+    /// An operator this builder has no method for, taken as is.
+    pub fn raw(&mut self, operator: &str) -> &mut Self {
+        self.term(operator.into(), true)
+    }
+
+    fn nested<F>(f: F) -> Vec<Clause>
+    where
+        F: FnOnce(&mut QueryBuilder) -> &mut QueryBuilder,
+    {
+        let mut query = QueryBuilder::default();
+        f(&mut query);
+
+        query.clauses
+    }
+
+    /// Matches when any of the clauses does.
+    pub fn any<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut QueryBuilder) -> &mut QueryBuilder,
+    {
+        self.clauses.push(Clause::Or(Self::nested(f)));
+        self
+    }
+
+    /// Parentheses around the clauses.
+    pub fn group<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut QueryBuilder) -> &mut QueryBuilder,
+    {
+        self.clauses.push(Clause::Group(Self::nested(f)));
+        self
+    }
+
+    /// Negates each of the clauses, `-a -b` matches when neither does. Twitter
+    /// doesn't negate groups, so a group or `any` in here is reported by `build`.
+    pub fn not<F>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(&mut QueryBuilder) -> &mut QueryBuilder,
+    {
+        for clause in Self::nested(f) {
+            self.clauses.push(Clause::Not(Box::new(clause)));
+        }
+        self
+    }
+
+    /// The query string, or every reason Twitter would reject it or never match
+    /// anything with it.
+    pub fn build(&self, tier: AccessTier) -> Result<String, Error> {
+        let mut problems = Vec::new();
+
+        if self.clauses.is_empty() {
+            problems.push("the query is empty".to_string());
+        }
+        for clause in &self.clauses {
+            clause.problems(false, &mut problems);
+        }
+        if !self.clauses.is_empty() && !self.clauses.iter().any(Clause::standalone) {
+            problems.push(
+                "negations and is:, has:, lang: and place_country: need a keyword or an operator like from: to go with"
+                    .to_string(),
+            );
+        }
+
+        let query = render_all(&self.clauses, " ");
+        let length = query.chars().count();
+        if length > tier.max_length() {
+            problems.push(format!(
+                "the query is {} characters long, the limit is {}",
+                length,
+                tier.max_length()
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(query)
+        } else {
+            Err(Error::InvalidQuery(problems))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build<F>(f: F) -> Result<String, Vec<String>>
+    where
+        F: FnOnce(&mut QueryBuilder) -> &mut QueryBuilder,
+    {
+        let mut query = QueryBuilder::default();
+        f(&mut query);
+
+        match query.build(AccessTier::Basic) {
+            Ok(query) => Ok(query),
+            Err(Error::InvalidQuery(problems)) => Err(problems),
+            Err(err) => panic!("unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn renders_clauses() {
+        assert_eq!(
+            build(|q| q
+                .keyword("rust")
+                .keyword("async await")
+                .from("@rustlang")
+                .any(|q| q.hashtag("rustlang").cashtag("$RUST"))
+                .has(Has::Links)
+                .url("https://www.rust-lang.org")),
+            Ok("rust \"async await\" from:rustlang (#rustlang OR $RUST) has:links url:\"https://www.rust-lang.org\"".to_string())
+        );
+    }
+
+    #[test]
+    fn negates_each_clause() {
+        assert_eq!(
+            build(|q| q
+                .keyword("rust")
+                .not(|q| q.is(Is::Retweet).is(Is::Nullcast))),
+            Ok("rust -is:retweet -is:nullcast".to_string())
+        );
+        assert!(build(|q| q
+            .keyword("rust")
+            .not(|q| q.any(|q| q.keyword("a").keyword("b"))))
+        .is_err());
+    }
+
+    #[test]
+    fn nullcast_has_to_be_negated() {
+        assert!(build(|q| q.keyword("rust").is(Is::Nullcast)).is_err());
+        assert!(build(|q| q.keyword("rust").group(|q| q.is(Is::Nullcast))).is_err());
+    }
+
+    #[test]
+    fn rejects_values_with_whitespace_or_quotes() {
+        assert!(build(|q| q.from("rust lang")).is_err());
+        assert!(build(|q| q.hashtag("rust\"lang")).is_err());
+        assert!(build(|q| q.url("https://example.com/a b")).is_err());
+        assert!(build(|q| q.keyword("rust").lang("e n")).is_err());
+        assert!(build(|q| q.conversation_id("")).is_err());
+        // keywords are quoted into phrases instead
+        assert_eq!(
+            build(|q| q.keyword("say \"hi\"")),
+            Ok("\"say \\\"hi\\\"\"".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_bare_boolean_operators() {
+        assert!(build(|q| q.keyword("rust").keyword("OR").keyword("go")).is_err());
+        assert!(build(|q| q.keyword("AND")).is_err());
+        assert_eq!(build(|q| q.keyword("or")), Ok("or".to_string()));
+        assert_eq!(build(|q| q.phrase("OR")), Ok("\"OR\"".to_string()));
+    }
+
+    #[test]
+    fn needs_a_standalone_clause() {
+        assert!(build(|q| q).is_err());
+        assert!(build(|q| q.has(Has::Media).not(|q| q.keyword("rust"))).is_err());
+        assert!(build(|q| q.keyword("rust").has(Has::Media)).is_ok());
+    }
+
+    #[test]
+    fn checks_length_per_tier() {
+        let mut query = QueryBuilder::default();
+        query.keyword(&"a".repeat(600));
+
+        assert!(query.build(AccessTier::Basic).is_err());
+        assert!(query.build(AccessTier::Pro).is_ok());
+    }
+}
@@ -0,0 +1,42 @@
+use reqwest::header::HeaderMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The `x-rate-limit-*` headers of the last response from an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    limit: u32,
+    remaining: u32,
+    reset: SystemTime,
+}
+impl RateLimit {
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let get = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+
+        Some(Self {
+            limit: get("x-rate-limit-limit")? as u32,
+            remaining: get("x-rate-limit-remaining")? as u32,
+            reset: UNIX_EPOCH + Duration::from_secs(get("x-rate-limit-reset")?),
+        })
+    }
+
+    /// How many requests are allowed in the current window.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// When the current window ends and `remaining` goes back to `limit`.
+    pub fn reset(&self) -> SystemTime {
+        self.reset
+    }
+
+    /// Time left until [`RateLimit::reset`], zero if it already passed.
+    pub fn reset_in(&self) -> Duration {
+        self.reset
+            .duration_since(SystemTime::now())
+            .unwrap_or_default()
+    }
+}
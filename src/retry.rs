@@ -0,0 +1,206 @@
+use crate::ratelimit::RateLimit;
+
+use rand::Rng;
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// When and how long to wait before sending a request again after a `429 Too Many
+/// Requests` or a `5xx` response. `5xx` responses are only retried for `GET`, `PUT`
+/// and `DELETE` requests, see [`RetryPolicy::retry_non_idempotent`].
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    honor_reset: bool,
+    non_idempotent: bool,
+}
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            jitter: true,
+            honor_reset: true,
+            non_idempotent: false,
+        }
+    }
+}
+impl RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub fn max_attempts(&mut self, max_attempts: u32) -> &mut Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Delay before the first retry, doubled on every retry after that.
+    pub fn base_delay(&mut self, base_delay: Duration) -> &mut Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Longest the client will wait before a retry. If the rate limit resets later
+    /// than that, the `TooManyRequests` error is returned instead.
+    pub fn max_delay(&mut self, max_delay: Duration) -> &mut Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Randomize every backoff delay between half and all of it.
+    pub fn jitter(&mut self, jitter: bool) -> &mut Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Wait for `Retry-After` (in seconds or as an HTTP date) or `x-rate-limit-reset`
+    /// instead of backing off when the response has them.
+    pub fn honor_reset(&mut self, honor_reset: bool) -> &mut Self {
+        self.honor_reset = honor_reset;
+        self
+    }
+
+    /// Also retry `POST` requests after a `5xx` response. A `5xx` doesn't mean the
+    /// request failed, e.g. the tweet may have been posted anyway, so this can post
+    /// it twice. Off by default.
+    pub fn retry_non_idempotent(&mut self, non_idempotent: bool) -> &mut Self {
+        self.non_idempotent = non_idempotent;
+        self
+    }
+
+    /// How long to wait before retrying, `None` if the response shouldn't be retried.
+    pub(crate) fn delay(
+        &self,
+        attempt: u32,
+        method: &Method,
+        status: StatusCode,
+        headers: &HeaderMap,
+        rate_limit: Option<RateLimit>,
+    ) -> Option<Duration> {
+        if attempt + 1 >= self.max_attempts {
+            return None;
+        }
+        if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+            return None;
+        }
+        // a 429 was never handled, but after a 5xx the request may have gone through
+        let idempotent = matches!(*method, Method::GET | Method::PUT | Method::DELETE);
+        if status.is_server_error() && !idempotent && !self.non_idempotent {
+            return None;
+        }
+
+        let retry_after = headers
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| match value.trim().parse::<u64>() {
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(_) => parse_http_date(value)
+                    .map(|at| at.duration_since(SystemTime::now()).unwrap_or_default()),
+            });
+        let reset = match status {
+            StatusCode::TOO_MANY_REQUESTS => {
+                retry_after.or(rate_limit.map(|limit| limit.reset_in() + Duration::from_secs(1)))
+            }
+            _ => retry_after,
+        };
+
+        let delay = match reset {
+            Some(reset) if self.honor_reset => reset,
+            _ => {
+                let backoff = self
+                    .base_delay
+                    .saturating_mul(2u32.saturating_pow(attempt))
+                    .min(self.max_delay);
+
+                if self.jitter {
+                    rand::thread_rng().gen_range(backoff / 2..=backoff)
+                } else {
+                    backoff
+                }
+            }
+        };
+
+        (delay <= self.max_delay).then_some(delay)
+    }
+}
+
+// IMF-fixdate of RFC 9110, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`. The obsolete
+// formats aren't sent by Twitter.
+fn parse_http_date(date: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let parts = date.split_whitespace().collect::<Vec<_>>();
+    let [_, day, month, year, time, "GMT"] = parts[..] else {
+        return None;
+    };
+    let day = day.parse::<u64>().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u64 + 1;
+    let year = year.parse::<u64>().ok()?;
+    let mut time = time.split(':').map(|n| n.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    if !(1..=31).contains(&day) || year < 1970 || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    // days since the epoch of a proleptic gregorian date, years starting in March
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = (y % 400) * 365 + (y % 400) / 4 - (y % 400) / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86400 + hours * 3600 + minutes * 60 + seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_date() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(784111777))
+        );
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(1709164800))
+        );
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("120"), None);
+    }
+
+    #[test]
+    fn server_errors_of_posts_are_not_retried() {
+        let headers = HeaderMap::new();
+        let policy = RetryPolicy::default();
+        let status = StatusCode::SERVICE_UNAVAILABLE;
+
+        assert!(policy
+            .delay(0, &Method::GET, status, &headers, None)
+            .is_some());
+        assert!(policy
+            .delay(0, &Method::POST, status, &headers, None)
+            .is_none());
+        assert!(policy
+            .delay(
+                0,
+                &Method::POST,
+                StatusCode::TOO_MANY_REQUESTS,
+                &headers,
+                None
+            )
+            .is_some());
+        assert!(RetryPolicy::default()
+            .retry_non_idempotent(true)
+            .delay(0, &Method::POST, status, &headers, None)
+            .is_some());
+    }
+}
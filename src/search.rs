@@ -0,0 +1,158 @@
+//! Options of the search and counts endpoints.
+
+use futures::Stream;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::paginate::Paginator;
+use crate::TwitterResponse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Newest first, the default.
+    Recency,
+    Relevancy,
+}
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Recency => "recency",
+            SortOrder::Relevancy => "relevancy",
+        }
+    }
+}
+
+/// The time and id range of a search, count or timeline. Times are ISO 8601 in
+/// UTC, e.g. `2023-05-01T12:00:00Z`.
+pub trait TimeBounds {
+    /// The query parameters the bounds are written to.
+    fn params(&mut self) -> &mut HashMap<&'static str, String>;
+
+    /// Oldest time to match, inclusive.
+    fn start_time(&mut self, time: &str) -> &mut Self {
+        self.params().insert("start_time", time.into());
+        self
+    }
+
+    /// Newest time to match, exclusive.
+    fn end_time(&mut self, time: &str) -> &mut Self {
+        self.params().insert("end_time", time.into());
+        self
+    }
+
+    /// Only match tweets newer than this one, e.g. the newest one of the last poll.
+    fn since_id(&mut self, id: &str) -> &mut Self {
+        self.params().insert("since_id", id.into());
+        self
+    }
+
+    /// Only match tweets older than this one.
+    fn until_id(&mut self, id: &str) -> &mut Self {
+        self.params().insert("until_id", id.into());
+        self
+    }
+}
+
+/// Takes the [`TimeBounds`] options.
+#[derive(Debug, Clone, Default)]
+pub struct SearchBuilder(pub HashMap<&'static str, String>);
+impl SearchBuilder {
+    pub fn sort_order(&mut self, order: SortOrder) -> &mut Self {
+        self.0.insert("sort_order", order.as_str().into());
+        self
+    }
+}
+impl TimeBounds for SearchBuilder {
+    fn params(&mut self) -> &mut HashMap<&'static str, String> {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Minute,
+    /// The default.
+    Hour,
+    Day,
+}
+impl Granularity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Granularity::Minute => "minute",
+            Granularity::Hour => "hour",
+            Granularity::Day => "day",
+        }
+    }
+}
+
+/// Like [`SearchBuilder`], for counts.
+#[derive(Debug, Clone, Default)]
+pub struct CountsBuilder(pub HashMap<&'static str, String>);
+impl CountsBuilder {
+    pub fn granularity(&mut self, granularity: Granularity) -> &mut Self {
+        self.0.insert("granularity", granularity.as_str().into());
+        self
+    }
+}
+impl TimeBounds for CountsBuilder {
+    fn params(&mut self) -> &mut HashMap<&'static str, String> {
+        &mut self.0
+    }
+}
+
+/// How many tweets matched between `start` and `end`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TweetCount {
+    start: String,
+    end: String,
+    tweet_count: u64,
+}
+impl TweetCount {
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    pub fn end(&self) -> &str {
+        &self.end
+    }
+
+    pub fn tweet_count(&self) -> u64 {
+        self.tweet_count
+    }
+}
+
+/// Pages of [`TweetCount`]s. Unlike a [`Paginator`] it can't set `max_results` or
+/// fields, the counts endpoints reject both.
+#[derive(Clone)]
+pub struct Counts(Paginator<TweetCount>);
+impl Counts {
+    pub(crate) fn new(paginator: Paginator<TweetCount>) -> Self {
+        Self(paginator)
+    }
+
+    /// Stop after this many counts in total.
+    pub fn limit(&mut self, limit: usize) -> &mut Self {
+        self.0.limit(limit);
+        self
+    }
+
+    /// Start from a token saved from [`Meta::next_token`](crate::Meta::next_token)
+    /// instead of the first page.
+    pub fn resume(&mut self, token: &str) -> &mut Self {
+        self.0.resume(token);
+        self
+    }
+
+    /// Every page, see [`Paginator::pages`].
+    pub fn pages(
+        &self,
+    ) -> impl Stream<Item = Result<TwitterResponse<Vec<TweetCount>>, Error>> + Send {
+        self.0.pages()
+    }
+
+    /// Every count of every page, see [`Paginator::items`].
+    pub fn items(&self) -> impl Stream<Item = Result<TweetCount, Error>> + Send {
+        self.0.items()
+    }
+}
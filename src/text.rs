@@ -0,0 +1,946 @@
+//! Tweet length counting, following the twitter-text v3 configuration.
+//!
+//! Text is NFC normalized first. Code points in the ranges below weigh 1, everything
+//! else (CJK, most symbols) weighs 2, an emoji sequence (flags, keycaps, skin tones and
+//! zero width joiner sequences of Extended_Pictographic code points) weighs 2 no matter
+//! how many code points it has and every url counts as the length of a t.co link.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Longest a tweet can be, in weighted characters.
+pub const MAX_WEIGHTED_LENGTH: usize = 280;
+
+/// Every url is shortened to a t.co link of this length.
+pub const TRANSFORMED_URL_LENGTH: usize = 23;
+
+const SCALE: usize = 100;
+const DEFAULT_WEIGHT: usize = 200;
+const RANGES: [(u32, u32, usize); 4] = [
+    (0, 4351, 100),
+    (8192, 8205, 100),
+    (8208, 8223, 100),
+    (8242, 8247, 100),
+];
+
+/// The result of counting a text the way Twitter does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedText {
+    weighted_length: usize,
+    valid: bool,
+}
+impl ParsedText {
+    pub fn weighted_length(&self) -> usize {
+        self.weighted_length
+    }
+
+    /// Not empty, no longer than [`MAX_WEIGHTED_LENGTH`] and without characters Twitter rejects.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Characters left before [`MAX_WEIGHTED_LENGTH`], negative once it's exceeded.
+    pub fn remaining(&self) -> i64 {
+        MAX_WEIGHTED_LENGTH as i64 - self.weighted_length as i64
+    }
+}
+
+pub fn parse(text: &str) -> ParsedText {
+    let chars = text.nfc().collect::<Vec<char>>();
+    let urls = find_urls(&chars);
+
+    let mut weight = 0;
+    let mut valid = !chars.is_empty();
+    let mut i = 0;
+    while i < chars.len() {
+        if let Some(&(_, end)) = urls.iter().find(|(start, _)| *start == i) {
+            weight += TRANSFORMED_URL_LENGTH * SCALE;
+            i = end;
+            continue;
+        }
+
+        let emoji = emoji_len(&chars[i..]);
+        if emoji > 0 {
+            weight += DEFAULT_WEIGHT;
+            i += emoji;
+            continue;
+        }
+
+        let c = chars[i] as u32;
+        if matches!(c, 0xFFFE | 0xFEFF | 0xFFFF) {
+            valid = false;
+        }
+        weight += RANGES
+            .iter()
+            .find(|(start, end, _)| (*start..=*end).contains(&c))
+            .map_or(DEFAULT_WEIGHT, |(_, _, weight)| *weight);
+        i += 1;
+    }
+
+    let weighted_length = weight / SCALE;
+
+    ParsedText {
+        weighted_length,
+        valid: valid && weighted_length <= MAX_WEIGHTED_LENGTH,
+    }
+}
+
+pub fn weighted_length(text: &str) -> usize {
+    parse(text).weighted_length()
+}
+
+pub fn is_valid(text: &str) -> bool {
+    parse(text).is_valid()
+}
+
+/// Start and end (exclusive) char index of every url in `chars`.
+fn find_urls(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut urls = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        // only ascii letters and digits are taken as part of a word, `日本語http://t.co` has a url
+        let preceding = i.checked_sub(1).map(|p| chars[p]);
+        if preceding.is_some_and(|p| {
+            p.is_ascii_alphanumeric()
+                || matches!(p, '@' | '＠' | '$' | '#' | '＃' | '\u{202A}'..='\u{202E}')
+        }) {
+            i += 1;
+            continue;
+        }
+
+        match url_at(chars, i, preceding) {
+            Some(end) => {
+                urls.push((i, end));
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+
+    urls
+}
+
+fn url_at(chars: &[char], start: usize, preceding: Option<char>) -> Option<usize> {
+    let starts_with = |at: usize, s: &str| {
+        s.chars().enumerate().all(|(n, c)| {
+            chars
+                .get(at + n)
+                .is_some_and(|ch| ch.eq_ignore_ascii_case(&c))
+        })
+    };
+    let protocol = if starts_with(start, "https://") {
+        8
+    } else if starts_with(start, "http://") {
+        7
+    } else {
+        0
+    };
+    if protocol == 0 && preceding.is_some_and(|p| matches!(p, '-' | '_' | '.' | '/')) {
+        return None;
+    }
+
+    // labels of the domain, as (start, end) char indexes
+    let label_char = |c: char| {
+        c.is_ascii_alphanumeric()
+            || c == '-'
+            || (protocol > 0 && c.is_alphanumeric() && !c.is_ascii())
+    };
+    let mut labels = Vec::new();
+    let mut pos = start + protocol;
+    loop {
+        let label_start = pos;
+        while pos < chars.len() && label_char(chars[pos]) {
+            pos += 1;
+        }
+        if pos == label_start {
+            break;
+        }
+        labels.push((label_start, pos));
+        if chars.get(pos) == Some(&'.') && chars.get(pos + 1).is_some_and(|c| label_char(*c)) {
+            pos += 1;
+        } else {
+            break;
+        }
+    }
+
+    // the longest run of labels that ends in a known top level domain
+    let (tld_label, mut end, is_cctld) = (1..labels.len())
+        .rev()
+        .find_map(|n| tld_end(chars, labels[n]).map(|(end, is_cctld)| (n, end, is_cctld)))?;
+    let tld = chars[labels[tld_label].0..end]
+        .iter()
+        .collect::<String>()
+        .to_lowercase();
+
+    // port
+    if chars.get(end) == Some(&':') && chars.get(end + 1).is_some_and(char::is_ascii_digit) {
+        end += 1;
+        while chars.get(end).is_some_and(char::is_ascii_digit) {
+            end += 1;
+        }
+    }
+
+    let path_start = end;
+    if chars.get(end) == Some(&'/') {
+        end = path_end(chars, end);
+    }
+    if chars.get(end) == Some(&'?') {
+        end = query_end(chars, end);
+    }
+
+    // without a protocol `example.de` is not a url, but `t.co`, `www.example.de` and
+    // `example.de/page` are
+    let has_path = end > path_start;
+    if protocol == 0
+        && is_cctld
+        && tld_label == 1
+        && !has_path
+        && !matches!(tld.as_str(), "co" | "tv")
+    {
+        return None;
+    }
+
+    Some(end)
+}
+
+/// Where the longest top level domain at the start of `label` ends, and whether it is
+/// a country code. It can be followed by anything but `[0-9a-zA-Z@+-]`, so the
+/// `.com` of `example.com中国語` counts.
+fn tld_end(chars: &[char], (start, end): (usize, usize)) -> Option<(usize, bool)> {
+    (start + 1..=end).rev().find_map(|tld_end| {
+        if chars
+            .get(tld_end)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '@' | '+' | '-'))
+        {
+            return None;
+        }
+
+        let tld = chars[start..tld_end]
+            .iter()
+            .collect::<String>()
+            .to_lowercase();
+        if CCTLDS.binary_search(&tld.as_str()).is_ok() {
+            Some((tld_end, true))
+        } else if GTLDS.binary_search(&tld.as_str()).is_ok() {
+            Some((tld_end, false))
+        } else {
+            None
+        }
+    })
+}
+
+fn latin_accent(c: char) -> bool {
+    matches!(
+        c,
+        '\u{C0}'..='\u{D6}'
+            | '\u{D8}'..='\u{F6}'
+            | '\u{F8}'..='\u{24F}'
+            | '\u{253}'
+            | '\u{254}'
+            | '\u{256}'
+            | '\u{257}'
+            | '\u{259}'
+            | '\u{25B}'
+            | '\u{263}'
+            | '\u{268}'
+            | '\u{26F}'
+            | '\u{272}'
+            | '\u{289}'
+            | '\u{28B}'
+            | '\u{2BB}'
+            | '\u{300}'..='\u{36F}'
+            | '\u{1E00}'..='\u{1EFF}'
+    )
+}
+
+// letters a path can have besides ascii, cyrillic and latin with accents
+fn path_letter(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ('\u{400}'..='\u{4FF}').contains(&c) || latin_accent(c)
+}
+
+/// The end of the path starting at the `/` at `slash`. Parentheses have to be balanced
+/// and at most two deep, and trailing punctuation belongs to the sentence, not the url.
+fn path_end(chars: &[char], slash: usize) -> usize {
+    let mut open = Vec::new();
+    let mut end = slash + 1;
+    while let Some(&c) = chars.get(end) {
+        match c {
+            '(' if open.len() < 2 => open.push(end),
+            ')' if !open.is_empty() => {
+                open.pop();
+            }
+            '!' | '*' | '\'' | ';' | ':' | '=' | '+' | ',' | '.' | '$' | '/' | '%' | '#' | '['
+            | ']' | '-' | '\u{2013}' | '_' | '~' | '&' | '|' | '@' => {}
+            c if path_letter(c) => {}
+            _ => break,
+        }
+        end += 1;
+    }
+    if let Some(&unclosed) = open.first() {
+        end = unclosed;
+    }
+
+    while end > slash + 1 {
+        let last = chars[end - 1];
+        if path_letter(last) || matches!(last, '+' | '-' | '=' | '_' | '#' | '/' | ')') {
+            break;
+        }
+        end -= 1;
+    }
+
+    end
+}
+
+/// The end of the query starting at the `?` at `question_mark`, which isn't part of the
+/// url if nothing valid follows it.
+fn query_end(chars: &[char], question_mark: usize) -> usize {
+    let mut end = question_mark + 1;
+    while chars.get(end).is_some_and(|c| {
+        c.is_ascii_alphanumeric()
+            || matches!(
+                c,
+                '!' | '?'
+                    | '*'
+                    | '\''
+                    | '@'
+                    | '('
+                    | ')'
+                    | ';'
+                    | ':'
+                    | '&'
+                    | '='
+                    | '+'
+                    | '$'
+                    | '/'
+                    | '%'
+                    | '#'
+                    | '['
+                    | ']'
+                    | '-'
+                    | '_'
+                    | '.'
+                    | ','
+                    | '~'
+                    | '|'
+            )
+    }) {
+        end += 1;
+    }
+
+    while end > question_mark + 1 {
+        let last = chars[end - 1];
+        if last.is_ascii_alphanumeric() || matches!(last, '-' | '_' | '&' | '=' | '#' | '/') {
+            return end;
+        }
+        end -= 1;
+    }
+
+    question_mark
+}
+
+/// How many chars the emoji sequence at the start of `chars` spans, 0 if there is none.
+fn emoji_len(chars: &[char]) -> usize {
+    let cp = |i: usize| chars.get(i).map(|c| *c as u32);
+    let is_regional = |c: u32| (0x1F1E6..=0x1F1FF).contains(&c);
+
+    let Some(first) = cp(0) else {
+        return 0;
+    };
+
+    // flags
+    if is_regional(first) {
+        return if cp(1).is_some_and(is_regional) { 2 } else { 1 };
+    }
+
+    // keycaps like 1️⃣
+    if matches!(chars[0], '0'..='9' | '#' | '*') {
+        return match (cp(1), cp(2)) {
+            (Some(0xFE0F), Some(0x20E3)) => 3,
+            (Some(0x20E3), _) => 2,
+            _ => 0,
+        };
+    }
+
+    let mut len = element_len(chars);
+    if len == 0 {
+        return 0;
+    }
+
+    // zero width joiner sequences like 👩‍💻
+    while cp(len) == Some(0x200D) {
+        let next = element_len(&chars[len + 1..]);
+        if next == 0 {
+            break;
+        }
+        len += 1 + next;
+    }
+
+    len
+}
+
+// a single emoji with its variation selector, skin tone and tags
+fn element_len(chars: &[char]) -> usize {
+    let cp = |i: usize| chars.get(i).map(|c| *c as u32);
+
+    let Some(first) = cp(0) else {
+        return 0;
+    };
+    // © and ® are plain text unless they ask for emoji presentation
+    let presentation = cp(1) == Some(0xFE0F);
+    if !is_pictographic(first) || (first <= 0xFF && !presentation) {
+        return 0;
+    }
+
+    let mut len = 1;
+    if cp(len) == Some(0xFE0F) {
+        len += 1;
+    }
+    if cp(len).is_some_and(|c| (0x1F3FB..=0x1F3FF).contains(&c)) {
+        len += 1;
+    }
+    while cp(len).is_some_and(|c| (0xE0020..=0xE007F).contains(&c)) {
+        len += 1;
+    }
+
+    len
+}
+
+fn is_pictographic(c: u32) -> bool {
+    EXTENDED_PICTOGRAPHIC
+        .binary_search_by(|(start, end)| {
+            if *end < c {
+                std::cmp::Ordering::Less
+            } else if *start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Splits text into parts of at most `max_weighted_length`, breaking between sentences
+/// where possible, then between words, and only inside a word when it's too long by itself.
+pub fn split(text: &str, max_weighted_length: usize) -> Vec<String> {
+    let mut parts = Vec::new();
+    let rest = pack(
+        &sentences(text),
+        max_weighted_length,
+        0,
+        String::new(),
+        &mut parts,
+    );
+    push_part(&mut parts, &rest);
+
+    parts
+}
+
+// Fills `current` with `pieces`, pushing it to `parts` whenever it is full, and
+// returns the part that is still being filled so the next pieces can go in it too.
+fn pack(
+    pieces: &[&str],
+    max: usize,
+    level: usize,
+    mut current: String,
+    parts: &mut Vec<String>,
+) -> String {
+    for piece in pieces {
+        let candidate = format!("{}{}", current, piece);
+        if weighted_length(candidate.trim()) <= max {
+            current = candidate;
+            continue;
+        }
+
+        push_part(parts, &current);
+        current = String::new();
+
+        if weighted_length(piece.trim()) <= max || level >= 2 {
+            current = piece.to_string();
+        } else if level == 0 {
+            let words = piece
+                .split_inclusive(char::is_whitespace)
+                .collect::<Vec<_>>();
+            current = pack(&words, max, 1, current, parts);
+        } else {
+            let chars = piece
+                .char_indices()
+                .map(|(i, c)| &piece[i..i + c.len_utf8()])
+                .collect::<Vec<_>>();
+            current = pack(&chars, max, 2, current, parts);
+        }
+    }
+
+    current
+}
+
+fn push_part(parts: &mut Vec<String>, part: &str) {
+    if !part.trim().is_empty() {
+        parts.push(part.trim().to_string());
+    }
+}
+
+// sentences keep the whitespace that follows them
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut ended = false;
+    for (i, c) in text.char_indices() {
+        if ended && !c.is_whitespace() {
+            sentences.push(&text[start..i]);
+            start = i;
+            ended = false;
+        }
+        if matches!(c, '…' | '。' | '！' | '？' | '\n') {
+            ended = true;
+        } else if matches!(c, '.' | '!' | '?') {
+            // only before whitespace, `example.com` or `3.5` go on
+            ended = text[i + 1..].starts_with(char::is_whitespace);
+        }
+    }
+    if start < text.len() {
+        sentences.push(&text[start..]);
+    }
+
+    sentences
+}
+
+// Top level domains of the root zone from the ICANN section of the public suffix list,
+// sorted for binary search. Country codes are the two letter ones and their
+// internationalized versions.
+#[rustfmt::skip]
+const CCTLDS: &[&str] = &[
+    "ac", "ad", "ae", "af", "ag", "ai", "al", "am", "ao", "aq", "ar", "as", "at", "au",
+    "aw", "ax", "az", "ba", "bb", "bd", "be", "bf", "bg", "bh", "bi", "bj", "bm", "bn",
+    "bo", "br", "bs", "bt", "bv", "bw", "by", "bz", "ca", "cc", "cd", "cf", "cg", "ch",
+    "ci", "ck", "cl", "cm", "cn", "co", "cr", "cu", "cv", "cw", "cx", "cy", "cz", "de",
+    "dj", "dk", "dm", "do", "dz", "ec", "ee", "eg", "er", "es", "et", "eu", "fi", "fj",
+    "fk", "fm", "fo", "fr", "ga", "gb", "gd", "ge", "gf", "gg", "gh", "gi", "gl", "gm",
+    "gn", "gp", "gq", "gr", "gs", "gt", "gu", "gw", "gy", "hk", "hm", "hn", "hr", "ht",
+    "hu", "id", "ie", "il", "im", "in", "io", "iq", "ir", "is", "it", "je", "jm", "jo",
+    "jp", "ke", "kg", "kh", "ki", "km", "kn", "kp", "kr", "kw", "ky", "kz", "la", "lb",
+    "lc", "li", "lk", "lr", "ls", "lt", "lu", "lv", "ly", "ma", "mc", "md", "me", "mg",
+    "mh", "mk", "ml", "mm", "mn", "mo", "mp", "mq", "mr", "ms", "mt", "mu", "mv", "mw",
+    "mx", "my", "mz", "na", "nc", "ne", "nf", "ng", "ni", "nl", "no", "np", "nr", "nu",
+    "nz", "om", "pa", "pe", "pf", "pg", "ph", "pk", "pl", "pm", "pn", "pr", "ps", "pt",
+    "pw", "py", "qa", "re", "ro", "rs", "ru", "rw", "sa", "sb", "sc", "sd", "se", "sg",
+    "sh", "si", "sj", "sk", "sl", "sm", "sn", "so", "sr", "ss", "st", "su", "sv", "sx",
+    "sy", "sz", "tc", "td", "tf", "tg", "th", "tj", "tk", "tl", "tm", "tn", "to", "tr",
+    "tt", "tv", "tw", "tz", "ua", "ug", "uk", "us", "uy", "uz", "va", "vc", "ve", "vg",
+    "vi", "vn", "vu", "wf", "ws", "ye", "yt", "za", "zm", "zw", "ελ", "ευ", "бг", "бел",
+    "ею", "мкд", "мон", "рф", "срб", "укр", "қаз", "հայ", "ישראל", "الاردن", "البحرين",
+    "الجزائر", "السعودية", "السعوديه", "السعودیة", "السعودیۃ", "المغرب", "اليمن", "امارات",
+    "ايران", "ایران", "بارت", "بھارت", "تونس", "سودان", "سوريا", "سورية", "عراق", "عمان",
+    "فلسطين", "قطر", "مصر", "مليسيا", "موريتانيا", "پاكستان", "پاکستان", "ڀارت", "भारत",
+    "भारतम्", "भारोत", "বাংলা", "ভারত", "ভাৰত", "ਭਾਰਤ", "ભારત", "ଭାରତ", "இந்தியா", "இலங்கை",
+    "சிங்கப்பூர்", "భారత్", "ಭಾರತ", "ഭാരതം", "ලංකා", "ไทย", "ລາວ", "გე", "中国", "中國", "台湾",
+    "台灣", "新加坡", "澳門", "澳门", "臺灣", "香港", "한국",
+];
+
+#[rustfmt::skip]
+const GTLDS: &[&str] = &[
+    "aaa", "aarp", "abarth", "abb", "abbott", "abbvie", "abc", "able", "abogado",
+    "abudhabi", "academy", "accenture", "accountant", "accountants", "aco", "actor", "ads",
+    "adult", "aeg", "aero", "aetna", "afl", "africa", "agakhan", "agency", "aig", "airbus",
+    "airforce", "airtel", "akdn", "alfaromeo", "alibaba", "alipay", "allfinanz", "allstate",
+    "ally", "alsace", "alstom", "amazon", "americanexpress", "americanfamily", "amex",
+    "amfam", "amica", "amsterdam", "analytics", "android", "anquan", "anz", "aol",
+    "apartments", "app", "apple", "aquarelle", "arab", "aramco", "archi", "army", "arpa",
+    "art", "arte", "asda", "asia", "associates", "athleta", "attorney", "auction", "audi",
+    "audible", "audio", "auspost", "author", "auto", "autos", "avianca", "aws", "axa",
+    "azure", "baby", "baidu", "banamex", "bananarepublic", "band", "bank", "bar",
+    "barcelona", "barclaycard", "barclays", "barefoot", "bargains", "baseball",
+    "basketball", "bauhaus", "bayern", "bbc", "bbt", "bbva", "bcg", "bcn", "beats",
+    "beauty", "beer", "bentley", "berlin", "best", "bestbuy", "bet", "bharti", "bible",
+    "bid", "bike", "bing", "bingo", "bio", "biz", "black", "blackfriday", "blockbuster",
+    "blog", "bloomberg", "blue", "bms", "bmw", "bnpparibas", "boats", "boehringer", "bofa",
+    "bom", "bond", "boo", "book", "booking", "bosch", "bostik", "boston", "bot", "boutique",
+    "box", "bradesco", "bridgestone", "broadway", "broker", "brother", "brussels", "build",
+    "builders", "business", "buy", "buzz", "bzh", "cab", "cafe", "cal", "call",
+    "calvinklein", "cam", "camera", "camp", "canon", "capetown", "capital", "capitalone",
+    "car", "caravan", "cards", "care", "career", "careers", "cars", "casa", "case", "cash",
+    "casino", "cat", "catering", "catholic", "cba", "cbn", "cbre", "cbs", "center", "ceo",
+    "cern", "cfa", "cfd", "chanel", "channel", "charity", "chase", "chat", "cheap",
+    "chintai", "christmas", "chrome", "church", "cipriani", "circle", "cisco", "citadel",
+    "citi", "citic", "city", "cityeats", "claims", "cleaning", "click", "clinic",
+    "clinique", "clothing", "cloud", "club", "clubmed", "coach", "codes", "coffee",
+    "college", "cologne", "com", "comcast", "commbank", "community", "company", "compare",
+    "computer", "comsec", "condos", "construction", "consulting", "contact", "contractors",
+    "cooking", "cookingchannel", "cool", "coop", "corsica", "country", "coupon", "coupons",
+    "courses", "cpa", "credit", "creditcard", "creditunion", "cricket", "crown", "crs",
+    "cruise", "cruises", "cuisinella", "cymru", "cyou", "dabur", "dad", "dance", "data",
+    "date", "dating", "datsun", "day", "dclk", "dds", "deal", "dealer", "deals", "degree",
+    "delivery", "dell", "deloitte", "delta", "democrat", "dental", "dentist", "desi",
+    "design", "dev", "dhl", "diamonds", "diet", "digital", "direct", "directory",
+    "discount", "discover", "dish", "diy", "dnp", "docs", "doctor", "dog", "domains", "dot",
+    "download", "drive", "dtv", "dubai", "dunlop", "dupont", "durban", "dvag", "dvr",
+    "earth", "eat", "eco", "edeka", "edu", "education", "email", "emerck", "energy",
+    "engineer", "engineering", "enterprises", "epson", "equipment", "ericsson", "erni",
+    "esq", "estate", "etisalat", "eurovision", "eus", "events", "exchange", "expert",
+    "exposed", "express", "extraspace", "fage", "fail", "fairwinds", "faith", "family",
+    "fan", "fans", "farm", "farmers", "fashion", "fast", "fedex", "feedback", "ferrari",
+    "ferrero", "fiat", "fidelity", "fido", "film", "final", "finance", "financial", "fire",
+    "firestone", "firmdale", "fish", "fishing", "fit", "fitness", "flickr", "flights",
+    "flir", "florist", "flowers", "fly", "foo", "food", "foodnetwork", "football", "ford",
+    "forex", "forsale", "forum", "foundation", "fox", "free", "fresenius", "frl", "frogans",
+    "frontdoor", "frontier", "ftr", "fujitsu", "fun", "fund", "furniture", "futbol", "fyi",
+    "gal", "gallery", "gallo", "gallup", "game", "games", "gap", "garden", "gay", "gbiz",
+    "gdn", "gea", "gent", "genting", "george", "ggee", "gift", "gifts", "gives", "giving",
+    "glass", "gle", "global", "globo", "gmail", "gmbh", "gmo", "gmx", "godaddy", "gold",
+    "goldpoint", "golf", "goo", "goodyear", "goog", "google", "gop", "got", "gov",
+    "grainger", "graphics", "gratis", "green", "gripe", "grocery", "group", "guardian",
+    "gucci", "guge", "guide", "guitars", "guru", "hair", "hamburg", "hangout", "haus",
+    "hbo", "hdfc", "hdfcbank", "health", "healthcare", "help", "helsinki", "here", "hermes",
+    "hgtv", "hiphop", "hisamitsu", "hitachi", "hiv", "hkt", "hockey", "holdings", "holiday",
+    "homedepot", "homegoods", "homes", "homesense", "honda", "horse", "hospital", "host",
+    "hosting", "hot", "hoteles", "hotels", "hotmail", "house", "how", "hsbc", "hughes",
+    "hyatt", "hyundai", "ibm", "icbc", "ice", "icu", "ieee", "ifm", "ikano", "imamat",
+    "imdb", "immo", "immobilien", "inc", "industries", "infiniti", "info", "ing", "ink",
+    "institute", "insurance", "insure", "int", "international", "intuit", "investments",
+    "ipiranga", "irish", "ismaili", "ist", "istanbul", "itau", "itv", "jaguar", "java",
+    "jcb", "jeep", "jetzt", "jewelry", "jio", "jll", "jmp", "jnj", "jobs", "joburg", "jot",
+    "joy", "jpmorgan", "jprs", "juegos", "juniper", "kaufen", "kddi", "kerryhotels",
+    "kerrylogistics", "kerryproperties", "kfh", "kia", "kids", "kim", "kinder", "kindle",
+    "kitchen", "kiwi", "koeln", "komatsu", "kosher", "kpmg", "kpn", "krd", "kred",
+    "kuokgroup", "kyoto", "lacaixa", "lamborghini", "lamer", "lancaster", "lancia", "land",
+    "landrover", "lanxess", "lasalle", "lat", "latino", "latrobe", "law", "lawyer", "lds",
+    "lease", "leclerc", "lefrak", "legal", "lego", "lexus", "lgbt", "lidl", "life",
+    "lifeinsurance", "lifestyle", "lighting", "like", "lilly", "limited", "limo", "lincoln",
+    "linde", "link", "lipsy", "live", "living", "llc", "llp", "loan", "loans", "locker",
+    "locus", "lol", "london", "lotte", "lotto", "love", "lpl", "lplfinancial", "ltd",
+    "ltda", "lundbeck", "luxe", "luxury", "macys", "madrid", "maif", "maison", "makeup",
+    "man", "management", "mango", "map", "market", "marketing", "markets", "marriott",
+    "marshalls", "maserati", "mattel", "mba", "mckinsey", "med", "media", "meet",
+    "melbourne", "meme", "memorial", "men", "menu", "merckmsd", "miami", "microsoft", "mil",
+    "mini", "mint", "mit", "mitsubishi", "mlb", "mls", "mma", "mobi", "mobile", "moda",
+    "moe", "moi", "mom", "monash", "money", "monster", "mormon", "mortgage", "moscow",
+    "moto", "motorcycles", "mov", "movie", "msd", "mtn", "mtr", "museum", "music", "mutual",
+    "nab", "nagoya", "name", "natura", "navy", "nba", "nec", "net", "netbank", "netflix",
+    "network", "neustar", "new", "news", "next", "nextdirect", "nexus", "nfl", "ngo", "nhk",
+    "nico", "nike", "nikon", "ninja", "nissan", "nissay", "nokia", "northwesternmutual",
+    "norton", "now", "nowruz", "nowtv", "nra", "nrw", "ntt", "nyc", "obi", "observer",
+    "office", "okinawa", "olayan", "olayangroup", "oldnavy", "ollo", "omega", "one", "ong",
+    "onion", "onl", "online", "ooo", "open", "oracle", "orange", "org", "organic",
+    "origins", "osaka", "otsuka", "ott", "ovh", "page", "panasonic", "paris", "pars",
+    "partners", "parts", "party", "passagens", "pay", "pccw", "pet", "pfizer", "pharmacy",
+    "phd", "philips", "phone", "photo", "photography", "photos", "physio", "pics", "pictet",
+    "pictures", "pid", "pin", "ping", "pink", "pioneer", "pizza", "place", "play",
+    "playstation", "plumbing", "plus", "pnc", "pohl", "poker", "politie", "porn", "post",
+    "pramerica", "praxi", "press", "prime", "pro", "prod", "productions", "prof",
+    "progressive", "promo", "properties", "property", "protection", "pru", "prudential",
+    "pub", "pwc", "qpon", "quebec", "quest", "racing", "radio", "read", "realestate",
+    "realtor", "realty", "recipes", "red", "redstone", "redumbrella", "rehab", "reise",
+    "reisen", "reit", "reliance", "ren", "rent", "rentals", "repair", "report",
+    "republican", "rest", "restaurant", "review", "reviews", "rexroth", "rich", "richardli",
+    "ricoh", "ril", "rio", "rip", "rocher", "rocks", "rodeo", "rogers", "room", "rsvp",
+    "rugby", "ruhr", "run", "rwe", "ryukyu", "saarland", "safe", "safety", "sakura", "sale",
+    "salon", "samsclub", "samsung", "sandvik", "sandvikcoromant", "sanofi", "sap", "sarl",
+    "sas", "save", "saxo", "sbi", "sbs", "sca", "scb", "schaeffler", "schmidt",
+    "scholarships", "school", "schule", "schwarz", "science", "scot", "search", "seat",
+    "secure", "security", "seek", "select", "sener", "services", "seven", "sew", "sex",
+    "sexy", "sfr", "shangrila", "sharp", "shaw", "shell", "shia", "shiksha", "shoes",
+    "shop", "shopping", "shouji", "show", "showtime", "silk", "sina", "singles", "site",
+    "ski", "skin", "sky", "skype", "sling", "smart", "smile", "sncf", "soccer", "social",
+    "softbank", "software", "sohu", "solar", "solutions", "song", "sony", "soy", "spa",
+    "space", "sport", "spot", "srl", "stada", "staples", "star", "statebank", "statefarm",
+    "stc", "stcgroup", "stockholm", "storage", "store", "stream", "studio", "study",
+    "style", "sucks", "supplies", "supply", "support", "surf", "surgery", "suzuki",
+    "swatch", "swiss", "sydney", "systems", "tab", "taipei", "talk", "taobao", "target",
+    "tatamotors", "tatar", "tattoo", "tax", "taxi", "tci", "tdk", "team", "tech",
+    "technology", "tel", "temasek", "tennis", "teva", "thd", "theater", "theatre", "tiaa",
+    "tickets", "tienda", "tiffany", "tips", "tires", "tirol", "tjmaxx", "tjx", "tkmaxx",
+    "tmall", "today", "tokyo", "tools", "top", "toray", "toshiba", "total", "tours", "town",
+    "toyota", "toys", "trade", "trading", "training", "travel", "travelchannel",
+    "travelers", "travelersinsurance", "trust", "trv", "tube", "tui", "tunes", "tushu",
+    "tvs", "ubank", "ubs", "unicom", "university", "uno", "uol", "ups", "vacations", "vana",
+    "vanguard", "vegas", "ventures", "verisign", "vermögensberater", "vermögensberatung",
+    "versicherung", "vet", "viajes", "video", "vig", "viking", "villas", "vin", "vip",
+    "virgin", "visa", "vision", "viva", "vivo", "vlaanderen", "vodka", "volkswagen",
+    "volvo", "vote", "voting", "voto", "voyage", "vuelos", "wales", "walmart", "walter",
+    "wang", "wanggou", "watch", "watches", "weather", "weatherchannel", "webcam", "weber",
+    "website", "wedding", "weibo", "weir", "whoswho", "wien", "wiki", "williamhill", "win",
+    "windows", "wine", "winners", "wme", "wolterskluwer", "woodside", "work", "works",
+    "world", "wow", "wtc", "wtf", "xbox", "xerox", "xfinity", "xihuan", "xin", "xxx", "xyz",
+    "yachts", "yahoo", "yamaxun", "yandex", "yodobashi", "yoga", "yokohama", "you",
+    "youtube", "yun", "zappos", "zara", "zero", "zip", "zone", "zuerich", "дети", "католик",
+    "ком", "москва", "онлайн", "орг", "рус", "сайт", "קום", "ابوظبي", "اتصالات", "ارامكو",
+    "العليان", "بازار", "بيتك", "شبكة", "عرب", "كاثوليك", "كوم", "موقع", "همراه", "कॉम",
+    "नेट", "संगठन", "คอม", "みんな", "アマゾン", "クラウド", "グーグル", "コム", "ストア", "セール", "ファッション",
+    "ポイント", "世界", "中信", "中文网", "亚马逊", "企业", "佛山", "信息", "健康", "八卦", "公司", "公益", "商城", "商店",
+    "商标", "嘉里", "嘉里大酒店", "在线", "大拿", "天主教", "娱乐", "家電", "广东", "微博", "慈善", "我爱你", "手机", "招聘",
+    "政务", "政府", "新闻", "时尚", "書籍", "机构", "淡马锡", "游戏", "点看", "移动", "组织机构", "网址", "网店", "网站",
+    "网络", "联通", "谷歌", "购物", "通販", "集团", "電訊盈科", "飞利浦", "食品", "餐厅", "香格里拉", "닷넷", "닷컴", "삼성",
+];
+
+// Extended_Pictographic code points of Unicode 14.0, emoji-data.txt
+#[rustfmt::skip]
+const EXTENDED_PICTOGRAPHIC: &[(u32, u32)] = &[
+    (0xA9, 0xA9), (0xAE, 0xAE), (0x203C, 0x203C), (0x2049, 0x2049), (0x2122, 0x2122),
+    (0x2139, 0x2139), (0x2194, 0x2199), (0x21A9, 0x21AA), (0x231A, 0x231B),
+    (0x2328, 0x2328), (0x2388, 0x2388), (0x23CF, 0x23CF), (0x23E9, 0x23F3),
+    (0x23F8, 0x23FA), (0x24C2, 0x24C2), (0x25AA, 0x25AB), (0x25B6, 0x25B6),
+    (0x25C0, 0x25C0), (0x25FB, 0x25FE), (0x2600, 0x2605), (0x2607, 0x2612),
+    (0x2614, 0x2685), (0x2690, 0x2705), (0x2708, 0x2712), (0x2714, 0x2714),
+    (0x2716, 0x2716), (0x271D, 0x271D), (0x2721, 0x2721), (0x2728, 0x2728),
+    (0x2733, 0x2734), (0x2744, 0x2744), (0x2747, 0x2747), (0x274C, 0x274C),
+    (0x274E, 0x274E), (0x2753, 0x2755), (0x2757, 0x2757), (0x2763, 0x2767),
+    (0x2795, 0x2797), (0x27A1, 0x27A1), (0x27B0, 0x27B0), (0x27BF, 0x27BF),
+    (0x2934, 0x2935), (0x2B05, 0x2B07), (0x2B1B, 0x2B1C), (0x2B50, 0x2B50),
+    (0x2B55, 0x2B55), (0x3030, 0x3030), (0x303D, 0x303D), (0x3297, 0x3297),
+    (0x3299, 0x3299), (0x1F000, 0x1F0FF), (0x1F10D, 0x1F10F), (0x1F12F, 0x1F12F),
+    (0x1F16C, 0x1F171), (0x1F17E, 0x1F17F), (0x1F18E, 0x1F18E), (0x1F191, 0x1F19A),
+    (0x1F1AD, 0x1F1E5), (0x1F201, 0x1F20F), (0x1F21A, 0x1F21A), (0x1F22F, 0x1F22F),
+    (0x1F232, 0x1F23A), (0x1F23C, 0x1F23F), (0x1F249, 0x1F3FA), (0x1F400, 0x1F53D),
+    (0x1F546, 0x1F64F), (0x1F680, 0x1F6FF), (0x1F774, 0x1F77F), (0x1F7D5, 0x1F7FF),
+    (0x1F80C, 0x1F80F), (0x1F848, 0x1F84F), (0x1F85A, 0x1F85F), (0x1F888, 0x1F88F),
+    (0x1F8AE, 0x1F8FF), (0x1F90C, 0x1F93A), (0x1F93C, 0x1F945), (0x1F947, 0x1FAFF),
+    (0x1FC00, 0x1FFFD),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(text: &str) -> Vec<String> {
+        let chars = text.nfc().collect::<Vec<char>>();
+        find_urls(&chars)
+            .into_iter()
+            .map(|(start, end)| chars[start..end].iter().collect())
+            .collect()
+    }
+
+    // WeightedTweetsWithDiscountedEmojiCounterTest of twitter-text's conformance/validate.yml
+    #[test]
+    fn validate_yml_weighted_length() {
+        let cases = [
+            ("This is a test.", 15, true),
+            ("Hi http://test.co", 26, true),
+            ("\u{1F637}\u{1F47E}\u{1F621}\u{1F525}\u{1F4A9}", 10, true),
+            ("\u{1F64B}\u{1F3FD}\u{1F468}\u{200D}\u{1F3A4}", 4, true),
+            (
+                "H\u{1F431}\u{263A}\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\u{1F466}",
+                7,
+                true,
+            ),
+            (
+                "\u{2002}\u{2003}\u{2004}\u{2005}\u{2010}\u{2014}\u{2032}",
+                7,
+                true,
+            ),
+            ("cafe\u{301}", 4, true),
+        ];
+        for (text, weighted_length, valid) in cases {
+            let parsed = parse(text);
+            assert_eq!(parsed.weighted_length(), weighted_length, "{:?}", text);
+            assert_eq!(parsed.is_valid(), valid, "{:?}", text);
+        }
+
+        assert!(is_valid(&"a".repeat(280)));
+        assert!(!is_valid(&"a".repeat(281)));
+        assert!(is_valid(&"\u{6545}".repeat(140)));
+        assert!(!is_valid(&"\u{6545}".repeat(141)));
+        assert!(!is_valid(""));
+        assert!(!is_valid("\u{FFFE}"));
+    }
+
+    #[test]
+    fn weighted_length_rules() {
+        assert_eq!(
+            weighted_length("\u{6545}\u{4E8B}\u{306E}\u{59CB}\u{307E}\u{308A}"),
+            12
+        );
+        assert!(is_valid(&"http://example.com/ ".repeat(11)));
+        assert_eq!(weighted_length(&"http://example.com/ ".repeat(13)), 13 * 24);
+    }
+
+    // urls of twitter-text's conformance/extract.yml
+    #[test]
+    fn extract_yml_urls() {
+        let cases: &[(&str, &[&str])] = &[
+            ("http://example.com", &["http://example.com"]),
+            ("text http://google.com", &["http://google.com"]),
+            ("text http://foobar.com/#", &["http://foobar.com/#"]),
+            ("text http://google.com/#foo", &["http://google.com/#foo"]),
+            (
+                "text http://google.com/#search?q=iphone%20-filter%3Alinks",
+                &["http://google.com/#search?q=iphone%20-filter%3Alinks"],
+            ),
+            (
+                "text http://somedomain.com/index.php?path=/abc/def/",
+                &["http://somedomain.com/index.php?path=/abc/def/"],
+            ),
+            (
+                "text http://www.boingboing.net/2007/02/14/katamari_damacy_phon.html",
+                &["http://www.boingboing.net/2007/02/14/katamari_damacy_phon.html"],
+            ),
+            ("text http://somehost.com:3000", &["http://somehost.com:3000"]),
+            ("text http://xo.com/~matthew+%-x", &["http://xo.com/~matthew+%-x"]),
+            (
+                "text http://en.wikipedia.org/wiki/Primer_(film)",
+                &["http://en.wikipedia.org/wiki/Primer_(film)"],
+            ),
+            (
+                "text http://www.ams.org/bookstore-getitem/item=mbk-59",
+                &["http://www.ams.org/bookstore-getitem/item=mbk-59"],
+            ),
+            ("text http://chilp.it/?77e8fd", &["http://chilp.it/?77e8fd"]),
+            ("text http://tell.me/why", &["http://tell.me/why"]),
+            ("text http://longtlds.info", &["http://longtlds.info"]),
+            ("text http://x.com/oneletterdomain", &["http://x.com/oneletterdomain"]),
+            (
+                "text http://msdn.microsoft.com/ja-jp/library/system.net.httpwebrequest(v=VS.100).aspx",
+                &["http://msdn.microsoft.com/ja-jp/library/system.net.httpwebrequest(v=VS.100).aspx"],
+            ),
+            ("text http://domain-begin_dash_2314352345_dfasd.foo-cow_4352.com", &[]),
+            ("text http://-doman_dash.com", &[]),
+            ("text http://no-tld", &[]),
+            ("text http://tld-too-short.x", &[]),
+            (
+                "foo.com foo.net foo.org foo.edu foo.gov",
+                &["foo.com", "foo.net", "foo.org", "foo.edu", "foo.gov"],
+            ),
+            (
+                "#test.com @test.com #http://test.com @http://test.com #t.co/abcde @t.co/abcde",
+                &[],
+            ),
+            (
+                "\u{3053}\u{308C}\u{306F}\u{65E5}\u{672C}\u{8A9E}\u{3067}\u{3059}\u{3002}example.com\u{4E2D}\u{56FD}\u{8A9E}http://t.co/abcde\u{D55C}\u{AD6D}\u{C5B4}http://example2.com",
+                &["example.com", "http://t.co/abcde", "http://example2.com"],
+            ),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(urls(text), *expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn urls_next_to_cjk() {
+        assert_eq!(
+            weighted_length("\u{65E5}\u{672C}\u{8A9E}http://t.co/abcde"),
+            29
+        );
+        assert_eq!(
+            weighted_length("\u{3053}\u{308C}\u{306F}\u{65E5}\u{672C}\u{8A9E}\u{3067}\u{3059}\u{3002}example.com\u{4E2D}\u{56FD}\u{8A9E}http://t.co/abcde\u{D55C}\u{AD6D}\u{C5B4}http://example2.com"),
+            99
+        );
+        assert_eq!(urls("http://example.com\u{3002}"), ["http://example.com"]);
+        // ascii letters still glue a url to the word before it
+        assert!(urls("foohttp://example.com").is_empty());
+    }
+
+    #[test]
+    fn urls_without_protocol() {
+        assert_eq!(weighted_length("see example.academy now"), 31);
+        assert_eq!(weighted_length("x.ninja"), 23);
+        assert_eq!(weighted_length("go to rust.studio/x"), 29);
+        assert_eq!(weighted_length("t.co"), 23);
+        assert_eq!(weighted_length("www.example.de"), 23);
+        // a bare country code domain isn't linked, `.js` isn't a country code at all
+        assert_eq!(weighted_length("example.de"), 10);
+        assert_eq!(weighted_length("foo.js/bar"), 10);
+        assert_eq!(
+            urls("foo.co.jp www.foo.co.uk foo.jp/ bar.jp/foo"),
+            ["foo.co.jp", "www.foo.co.uk", "foo.jp/", "bar.jp/foo"]
+        );
+        assert!(urls("foo.comm foo.somecom foo.govedu example.notatld").is_empty());
+        assert!(urls("@example.com foo@example.com example.com@foo").is_empty());
+    }
+
+    #[test]
+    fn url_paths_and_queries() {
+        assert_eq!(
+            urls("see http://example.com/path."),
+            ["http://example.com/path"]
+        );
+        assert_eq!(urls("(http://example.com/foo)"), ["http://example.com/foo"]);
+        assert_eq!(urls("http://example.com/a(b c"), ["http://example.com/a"]);
+        assert_eq!(urls("example.com?a=b&c=d. next"), ["example.com?a=b&c=d"]);
+        assert_eq!(urls("what is example.com?"), ["example.com"]);
+        assert_eq!(urls("http://google.com#foo"), ["http://google.com"]);
+        assert_eq!(
+            urls("http://example.com/\u{43F}\u{443}\u{442}\u{44C}/caf\u{E9}"),
+            ["http://example.com/\u{43F}\u{443}\u{442}\u{44C}/caf\u{E9}"]
+        );
+    }
+
+    fn assert_parts(text: &str, max: usize, expected: &[&str]) {
+        let parts = split(text, max);
+        assert_eq!(parts, expected, "{:?}", text);
+        assert!(parts.iter().all(|part| weighted_length(part) <= max));
+    }
+
+    #[test]
+    fn split_between_sentences() {
+        assert_parts("Short enough.", 280, &["Short enough."]);
+        assert_parts("One. Two. Three.", 9, &["One. Two.", "Three."]);
+        assert_parts("Why? Because!\nOk.", 9, &["Why?", "Because!", "Ok."]);
+        assert_parts("", 10, &[]);
+        assert_parts(
+            "See example.com now. Or 3.5 later.",
+            40,
+            &["See example.com now.", "Or 3.5 later."],
+        );
+    }
+
+    #[test]
+    fn split_long_sentences_between_words() {
+        assert_parts(
+            "a sentence without any end in sight",
+            12,
+            &["a sentence", "without any", "end in sight"],
+        );
+        // the sentence after a split one goes in the same part if it fits
+        assert_parts(
+            "aaaa bbbb cccc. Dd. Ee.",
+            10,
+            &["aaaa bbbb", "cccc. Dd.", "Ee."],
+        );
+    }
+
+    #[test]
+    fn split_long_words_between_chars() {
+        assert_parts("abcdefghij", 4, &["abcd", "efgh", "ij"]);
+        assert_parts("ok abcdefghij ok", 4, &["ok", "abcd", "efgh", "ij", "ok"]);
+        // CJK weighs 2
+        assert_parts(
+            &"\u{6545}".repeat(5),
+            4,
+            &["\u{6545}\u{6545}", "\u{6545}\u{6545}", "\u{6545}"],
+        );
+    }
+
+    #[test]
+    fn split_parts_fit() {
+        let text =
+            "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do eiusmod tempor \
+            incididunt ut labore et dolore magna aliqua! Ut enim ad minim veniam? \
+            https://example.com/a/long/path \u{6545}\u{4E8B} Duis aute irure dolor.";
+        // urls weigh 23 and are only cut when `max` is shorter than that
+        for max in [24, 40, 80, 280] {
+            let parts = split(text, max);
+            assert!(
+                parts.iter().all(|part| weighted_length(part) <= max),
+                "{:?}",
+                parts
+            );
+            assert_eq!(
+                parts.join(" ").split_whitespace().collect::<Vec<_>>(),
+                text.split_whitespace().collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn emoji_sequences() {
+        // flag, keycap and tag sequence
+        assert_eq!(weighted_length("\u{1F1EF}\u{1F1F5}"), 2);
+        assert_eq!(weighted_length("1\u{FE0F}\u{20E3}"), 2);
+        assert_eq!(
+            weighted_length("\u{1F3F4}\u{E0067}\u{E0062}\u{E0073}\u{E0063}\u{E0074}\u{E007F}"),
+            2
+        );
+        // text presentation copyright sign is a latin-1 character, the emoji isn't
+        assert_eq!(weighted_length("\u{A9}"), 1);
+        assert_eq!(weighted_length("\u{A9}\u{FE0F}"), 2);
+        // melting face, heart hands with a skin tone
+        assert_eq!(weighted_length("\u{1FAE0}\u{1FAF6}\u{1F3FD}"), 4);
+    }
+}
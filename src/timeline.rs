@@ -0,0 +1,41 @@
+//! Options of the user tweet, mention and home timelines.
+
+use std::collections::HashMap;
+
+use crate::fields;
+use crate::search::TimeBounds;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exclude {
+    Retweets,
+    Replies,
+}
+impl Exclude {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Exclude::Retweets => "retweets",
+            Exclude::Replies => "replies",
+        }
+    }
+}
+impl std::fmt::Display for Exclude {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Takes the [`TimeBounds`] options.
+#[derive(Debug, Clone, Default)]
+pub struct TimelineBuilder(pub HashMap<&'static str, String>);
+impl TimelineBuilder {
+    /// Leave out retweets or replies. The mentions timeline doesn't support this.
+    pub fn exclude(&mut self, exclude: &[Exclude]) -> &mut Self {
+        self.0.insert("exclude", fields::join(exclude));
+        self
+    }
+}
+impl TimeBounds for TimelineBuilder {
+    fn params(&mut self) -> &mut HashMap<&'static str, String> {
+        &mut self.0
+    }
+}
@@ -0,0 +1,729 @@
+//! Tweets as returned by the v2 lookup, search and timeline endpoints, with the
+//! objects they reference in `includes`.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{ReplySettings, TwitterUserData};
+
+/// A tweet. Besides `id` and `text`, a field is only there when it was asked
+/// for with [`TweetField`](crate::fields::TweetField).
+#[derive(Debug, Clone, Deserialize)]
+pub struct Tweet {
+    id: String,
+    text: String,
+    author_id: Option<String>,
+    created_at: Option<String>,
+    conversation_id: Option<String>,
+    in_reply_to_user_id: Option<String>,
+    lang: Option<String>,
+    source: Option<String>,
+    possibly_sensitive: Option<bool>,
+    reply_settings: Option<ReplySettings>,
+    public_metrics: Option<TweetPublicMetrics>,
+    entities: Option<Entities>,
+    attachments: Option<Attachments>,
+    geo: Option<TweetGeo>,
+    edit_controls: Option<EditControls>,
+    withheld: Option<Withheld>,
+    #[serde(default)]
+    referenced_tweets: Vec<ReferencedTweet>,
+    #[serde(default)]
+    edit_history_tweet_ids: Vec<String>,
+    #[serde(default)]
+    context_annotations: Vec<ContextAnnotation>,
+}
+impl Tweet {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn author_id(&self) -> Option<&str> {
+        self.author_id.as_deref()
+    }
+
+    /// ISO 8601, e.g. `2023-05-01T12:00:00.000Z`.
+    pub fn created_at(&self) -> Option<&str> {
+        self.created_at.as_deref()
+    }
+
+    /// Id of the tweet that started the conversation.
+    pub fn conversation_id(&self) -> Option<&str> {
+        self.conversation_id.as_deref()
+    }
+
+    pub fn in_reply_to_user_id(&self) -> Option<&str> {
+        self.in_reply_to_user_id.as_deref()
+    }
+
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    pub fn possibly_sensitive(&self) -> Option<bool> {
+        self.possibly_sensitive
+    }
+
+    pub fn reply_settings(&self) -> Option<ReplySettings> {
+        self.reply_settings
+    }
+
+    pub fn public_metrics(&self) -> Option<&TweetPublicMetrics> {
+        self.public_metrics.as_ref()
+    }
+
+    pub fn entities(&self) -> Option<&Entities> {
+        self.entities.as_ref()
+    }
+
+    pub fn attachments(&self) -> Option<&Attachments> {
+        self.attachments.as_ref()
+    }
+
+    pub fn geo(&self) -> Option<&TweetGeo> {
+        self.geo.as_ref()
+    }
+
+    pub fn edit_controls(&self) -> Option<&EditControls> {
+        self.edit_controls.as_ref()
+    }
+
+    pub fn withheld(&self) -> Option<&Withheld> {
+        self.withheld.as_ref()
+    }
+
+    pub fn referenced_tweets(&self) -> &[ReferencedTweet] {
+        &self.referenced_tweets
+    }
+
+    /// Ids of every version of the tweet, oldest first.
+    pub fn edit_history_tweet_ids(&self) -> &[String] {
+        &self.edit_history_tweet_ids
+    }
+
+    pub fn context_annotations(&self) -> &[ContextAnnotation] {
+        &self.context_annotations
+    }
+}
+
+/// Whether and until when a tweet can still be edited.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EditControls {
+    edits_remaining: u32,
+    is_edit_eligible: bool,
+    editable_until: String,
+}
+impl EditControls {
+    pub fn edits_remaining(&self) -> u32 {
+        self.edits_remaining
+    }
+
+    pub fn is_edit_eligible(&self) -> bool {
+        self.is_edit_eligible
+    }
+
+    /// ISO 8601, e.g. `2023-05-01T12:30:00.000Z`.
+    pub fn editable_until(&self) -> &str {
+        &self.editable_until
+    }
+}
+
+/// Why and where a tweet or user is withheld.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Withheld {
+    #[serde(default)]
+    copyright: bool,
+    #[serde(default)]
+    country_codes: Vec<String>,
+}
+impl Withheld {
+    /// Whether it is withheld because of a copyright complaint, always `false`
+    /// for users.
+    pub fn copyright(&self) -> bool {
+        self.copyright
+    }
+
+    /// ISO 3166-1 alpha-2 codes of the countries it is withheld in.
+    pub fn country_codes(&self) -> &[String] {
+        &self.country_codes
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TweetPublicMetrics {
+    retweet_count: u64,
+    reply_count: u64,
+    like_count: u64,
+    quote_count: u64,
+    #[serde(default)]
+    bookmark_count: u64,
+    #[serde(default)]
+    impression_count: u64,
+}
+impl TweetPublicMetrics {
+    pub fn retweet_count(&self) -> u64 {
+        self.retweet_count
+    }
+
+    pub fn reply_count(&self) -> u64 {
+        self.reply_count
+    }
+
+    pub fn like_count(&self) -> u64 {
+        self.like_count
+    }
+
+    pub fn quote_count(&self) -> u64 {
+        self.quote_count
+    }
+
+    pub fn bookmark_count(&self) -> u64 {
+        self.bookmark_count
+    }
+
+    pub fn impression_count(&self) -> u64 {
+        self.impression_count
+    }
+}
+
+/// Urls, hashtags, mentions and so on found in a text. `start` and `end` are
+/// code point offsets into it.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Entities {
+    #[serde(default)]
+    urls: Vec<UrlEntity>,
+    #[serde(default)]
+    hashtags: Vec<TagEntity>,
+    #[serde(default)]
+    cashtags: Vec<TagEntity>,
+    #[serde(default)]
+    mentions: Vec<MentionEntity>,
+    #[serde(default)]
+    annotations: Vec<AnnotationEntity>,
+}
+impl Entities {
+    pub fn urls(&self) -> &[UrlEntity] {
+        &self.urls
+    }
+
+    pub fn hashtags(&self) -> &[TagEntity] {
+        &self.hashtags
+    }
+
+    pub fn cashtags(&self) -> &[TagEntity] {
+        &self.cashtags
+    }
+
+    pub fn mentions(&self) -> &[MentionEntity] {
+        &self.mentions
+    }
+
+    pub fn annotations(&self) -> &[AnnotationEntity] {
+        &self.annotations
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UrlEntity {
+    start: usize,
+    end: usize,
+    url: String,
+    expanded_url: Option<String>,
+    display_url: Option<String>,
+    unwound_url: Option<String>,
+    media_key: Option<String>,
+}
+impl UrlEntity {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The t.co link.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn expanded_url(&self) -> Option<&str> {
+        self.expanded_url.as_deref()
+    }
+
+    pub fn display_url(&self) -> Option<&str> {
+        self.display_url.as_deref()
+    }
+
+    /// Where the url leads after following redirects.
+    pub fn unwound_url(&self) -> Option<&str> {
+        self.unwound_url.as_deref()
+    }
+
+    /// Set when the url links to attached media.
+    pub fn media_key(&self) -> Option<&str> {
+        self.media_key.as_deref()
+    }
+}
+
+/// A hashtag or cashtag, without the `#` or `$`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TagEntity {
+    start: usize,
+    end: usize,
+    tag: String,
+}
+impl TagEntity {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MentionEntity {
+    start: usize,
+    end: usize,
+    username: String,
+    id: Option<String>,
+}
+impl MentionEntity {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+}
+
+/// A person, place, product or organization Twitter recognized in the text.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnnotationEntity {
+    start: usize,
+    end: usize,
+    probability: f64,
+    #[serde(rename = "type")]
+    kind: String,
+    normalized_text: String,
+}
+impl AnnotationEntity {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    /// `Person`, `Place`, `Product`, `Organization` or `Other`.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn normalized_text(&self) -> &str {
+        &self.normalized_text
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    RepliedTo,
+    Quoted,
+    Retweeted,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReferencedTweet {
+    #[serde(rename = "type")]
+    kind: ReferenceKind,
+    id: String,
+}
+impl ReferencedTweet {
+    pub fn kind(&self) -> ReferenceKind {
+        self.kind
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Keys of the media and ids of the poll attached to a tweet, see [`Includes`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Attachments {
+    #[serde(default)]
+    media_keys: Vec<String>,
+    #[serde(default)]
+    poll_ids: Vec<String>,
+}
+impl Attachments {
+    pub fn media_keys(&self) -> &[String] {
+        &self.media_keys
+    }
+
+    pub fn poll_ids(&self) -> &[String] {
+        &self.poll_ids
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TweetGeo {
+    place_id: Option<String>,
+    coordinates: Option<Value>,
+}
+impl TweetGeo {
+    pub fn place_id(&self) -> Option<&str> {
+        self.place_id.as_deref()
+    }
+
+    /// A GeoJSON point, only there when the exact location was shared.
+    pub fn coordinates(&self) -> Option<&Value> {
+        self.coordinates.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContextAnnotation {
+    domain: ContextEntity,
+    entity: ContextEntity,
+}
+impl ContextAnnotation {
+    pub fn domain(&self) -> &ContextEntity {
+        &self.domain
+    }
+
+    pub fn entity(&self) -> &ContextEntity {
+        &self.entity
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContextEntity {
+    id: String,
+    name: String,
+    description: Option<String>,
+}
+impl ContextEntity {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+/// The objects requested with [`Expansion`](crate::fields::Expansion)s.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Includes {
+    #[serde(default)]
+    users: Vec<TwitterUserData>,
+    #[serde(default)]
+    tweets: Vec<Tweet>,
+    #[serde(default)]
+    media: Vec<Media>,
+    #[serde(default)]
+    places: Vec<Place>,
+    #[serde(default)]
+    polls: Vec<Poll>,
+}
+impl Includes {
+    pub fn users(&self) -> &[TwitterUserData] {
+        &self.users
+    }
+
+    pub fn tweets(&self) -> &[Tweet] {
+        &self.tweets
+    }
+
+    pub fn media(&self) -> &[Media] {
+        &self.media
+    }
+
+    pub fn places(&self) -> &[Place] {
+        &self.places
+    }
+
+    pub fn polls(&self) -> &[Poll] {
+        &self.polls
+    }
+
+    pub fn user(&self, id: &str) -> Option<&TwitterUserData> {
+        self.users.iter().find(|user| user.id() == id)
+    }
+
+    pub fn tweet(&self, id: &str) -> Option<&Tweet> {
+        self.tweets.iter().find(|tweet| tweet.id() == id)
+    }
+
+    pub fn media_by_key(&self, media_key: &str) -> Option<&Media> {
+        self.media
+            .iter()
+            .find(|media| media.media_key() == media_key)
+    }
+
+    pub fn place(&self, id: &str) -> Option<&Place> {
+        self.places.iter().find(|place| place.id() == id)
+    }
+
+    pub fn poll(&self, id: &str) -> Option<&Poll> {
+        self.polls.iter().find(|poll| poll.id() == id)
+    }
+
+    /// Adds the objects of `other` that aren't here yet, e.g. from the next page
+    /// of results.
+    pub fn extend(&mut self, other: Includes) {
+        fn merge<T, K: PartialEq>(into: &mut Vec<T>, from: Vec<T>, key: impl Fn(&T) -> K) {
+            for item in from {
+                if !into.iter().any(|existing| key(existing) == key(&item)) {
+                    into.push(item);
+                }
+            }
+        }
+
+        merge(&mut self.users, other.users, |user| user.id().to_string());
+        merge(&mut self.tweets, other.tweets, |tweet| {
+            tweet.id().to_string()
+        });
+        merge(&mut self.media, other.media, |media| {
+            media.media_key().to_string()
+        });
+        merge(&mut self.places, other.places, |place| {
+            place.id().to_string()
+        });
+        merge(&mut self.polls, other.polls, |poll| poll.id().to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+            && self.tweets.is_empty()
+            && self.media.is_empty()
+            && self.places.is_empty()
+            && self.polls.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Media {
+    media_key: String,
+    #[serde(rename = "type")]
+    kind: String,
+    url: Option<String>,
+    preview_image_url: Option<String>,
+    alt_text: Option<String>,
+    duration_ms: Option<u64>,
+    width: Option<u32>,
+    height: Option<u32>,
+    public_metrics: Option<MediaPublicMetrics>,
+    #[serde(default)]
+    variants: Vec<MediaVariant>,
+}
+impl Media {
+    pub fn media_key(&self) -> &str {
+        &self.media_key
+    }
+
+    /// `photo`, `video` or `animated_gif`.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Only set for photos, use [`Media::variants`] for videos.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    pub fn preview_image_url(&self) -> Option<&str> {
+        self.preview_image_url.as_deref()
+    }
+
+    pub fn alt_text(&self) -> Option<&str> {
+        self.alt_text.as_deref()
+    }
+
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.duration_ms
+    }
+
+    pub fn width(&self) -> Option<u32> {
+        self.width
+    }
+
+    pub fn height(&self) -> Option<u32> {
+        self.height
+    }
+
+    pub fn public_metrics(&self) -> Option<&MediaPublicMetrics> {
+        self.public_metrics.as_ref()
+    }
+
+    pub fn variants(&self) -> &[MediaVariant] {
+        &self.variants
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct MediaPublicMetrics {
+    #[serde(default)]
+    view_count: u64,
+}
+impl MediaPublicMetrics {
+    pub fn view_count(&self) -> u64 {
+        self.view_count
+    }
+}
+
+/// One encoding of a video or gif.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MediaVariant {
+    url: String,
+    content_type: String,
+    bit_rate: Option<u64>,
+}
+impl MediaVariant {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    pub fn bit_rate(&self) -> Option<u64> {
+        self.bit_rate
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Place {
+    id: String,
+    full_name: String,
+    name: Option<String>,
+    country: Option<String>,
+    country_code: Option<String>,
+    place_type: Option<String>,
+    geo: Option<Value>,
+    #[serde(default)]
+    contained_within: Vec<String>,
+}
+impl Place {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// e.g. `Manhattan, NY`.
+    pub fn full_name(&self) -> &str {
+        &self.full_name
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    /// ISO 3166-1 alpha-2.
+    pub fn country_code(&self) -> Option<&str> {
+        self.country_code.as_deref()
+    }
+
+    /// e.g. `city`, `admin` or `poi`.
+    pub fn place_type(&self) -> Option<&str> {
+        self.place_type.as_deref()
+    }
+
+    /// The bounding box as a GeoJSON feature.
+    pub fn geo(&self) -> Option<&Value> {
+        self.geo.as_ref()
+    }
+
+    pub fn contained_within(&self) -> &[String] {
+        &self.contained_within
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Poll {
+    id: String,
+    options: Vec<PollOption>,
+    duration_minutes: Option<u32>,
+    end_datetime: Option<String>,
+    voting_status: Option<String>,
+}
+impl Poll {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn options(&self) -> &[PollOption] {
+        &self.options
+    }
+
+    pub fn duration_minutes(&self) -> Option<u32> {
+        self.duration_minutes
+    }
+
+    pub fn end_datetime(&self) -> Option<&str> {
+        self.end_datetime.as_deref()
+    }
+
+    /// `open` or `closed`.
+    pub fn voting_status(&self) -> Option<&str> {
+        self.voting_status.as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PollOption {
+    position: u32,
+    label: String,
+    votes: u64,
+}
+impl PollOption {
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn votes(&self) -> u64 {
+        self.votes
+    }
+}